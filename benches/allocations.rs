@@ -0,0 +1,137 @@
+//! Counts the heap allocations performed while parsing large synthetic documents.
+//! Every document is parsed into owned values and into borrowed values.
+//!
+//! Run with `cargo bench --bench allocations`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use qt_json::elements::{JsonValue, Map, Object};
+use qt_json::{parse_borrowed, DocumentBuilder, QJSONDocument};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// An array of objects, each containing a small array of numbers
+fn wide_document() -> Vec<u8> {
+    let mut builder = DocumentBuilder::array();
+
+    for i in 0..2_000 {
+        let mut values = Map::new();
+        values.insert("id".to_string(), JsonValue::Number(i as f64));
+        values.insert(
+            "values".to_string(),
+            JsonValue::Array((0..10).map(|v| JsonValue::Number(v as f64)).collect()),
+        );
+
+        builder = builder.push(JsonValue::Object(Object { size: 2, values }));
+    }
+
+    builder.build().to_binary().unwrap()
+}
+
+/// Arrays nested into each other
+fn deep_document() -> Vec<u8> {
+    let mut value = JsonValue::Number(1.0);
+
+    for _ in 0..100 {
+        value = JsonValue::Array(vec![value, JsonValue::Bool(true)]);
+    }
+
+    DocumentBuilder::array()
+        .push(value)
+        .build()
+        .to_binary()
+        .unwrap()
+}
+
+/// An array of objects containing nothing but strings
+fn string_document() -> Vec<u8> {
+    let mut builder = DocumentBuilder::array();
+
+    for i in 0..2_000 {
+        let mut values = Map::new();
+        values.insert("name".to_string(), JsonValue::String(format!("user {}", i)));
+        values.insert(
+            "mail".to_string(),
+            JsonValue::String(format!("user{}@example.com", i)),
+        );
+        values.insert(
+            "city".to_string(),
+            JsonValue::String("Braunschweig".to_string()),
+        );
+
+        builder = builder.push(JsonValue::Object(Object { size: 3, values }));
+    }
+
+    builder.build().to_binary().unwrap()
+}
+
+fn measure(name: &str, data: &[u8]) {
+    let (allocations, bytes, elapsed) = count(|| QJSONDocument::try_from(data).unwrap());
+    report(name, "owned", data, allocations, bytes, elapsed);
+
+    let (allocations, bytes, elapsed) = count(|| parse_borrowed(data).unwrap());
+    report(name, "borrow", data, allocations, bytes, elapsed);
+}
+
+/// Runs the parser and returns the allocations, the allocated bytes and the time it took.
+/// The parsed value is dropped after the measurement stopped.
+fn count<T, F: FnOnce() -> T>(parse: F) -> (usize, usize, Duration) {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED_BYTES.store(0, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let parsed = parse();
+    let elapsed = start.elapsed();
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+
+    drop(parsed);
+
+    (allocations, bytes, elapsed)
+}
+
+fn report(
+    name: &str,
+    mode: &str,
+    data: &[u8],
+    allocations: usize,
+    bytes: usize,
+    elapsed: Duration,
+) {
+    println!(
+        "{:<6} {:<6} {:>9} bytes input: {:>8} allocations, {:>10} bytes allocated, {:?}",
+        name,
+        mode,
+        data.len(),
+        allocations,
+        bytes,
+        elapsed
+    );
+}
+
+fn main() {
+    measure("wide", &wide_document());
+    measure("deep", &deep_document());
+    measure("string", &string_document());
+}
@@ -0,0 +1,78 @@
+//! Measures the parse throughput on a few representative documents.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use qt_json::elements::JsonValue;
+use qt_json::{parse_borrowed, DocumentBuilder, QJSONDocument};
+
+/// An object with a handful of entries, like a small settings file
+fn small_document() -> Vec<u8> {
+    DocumentBuilder::object()
+        .insert("name", JsonValue::String("qt-json".to_string()))
+        .insert("version", JsonValue::Number(1.0))
+        .insert("ratio", JsonValue::Number(0.75))
+        .insert("enabled", JsonValue::Bool(true))
+        .insert("parent", JsonValue::Null)
+        .build()
+        .to_binary()
+        .unwrap()
+}
+
+/// An array of 10k numbers, half of them stored inline and half of them as doubles
+fn wide_document() -> Vec<u8> {
+    let mut builder = DocumentBuilder::array();
+
+    for i in 0..10_000 {
+        let number = match i % 2 {
+            0 => i as f64,
+            _ => i as f64 + 0.5,
+        };
+
+        builder = builder.push(JsonValue::Number(number));
+    }
+
+    builder.build().to_binary().unwrap()
+}
+
+/// Arrays nested into each other close to the default depth limit
+fn deep_document() -> Vec<u8> {
+    let mut value = JsonValue::Number(1.0);
+
+    for _ in 0..120 {
+        value = JsonValue::Array(vec![value, JsonValue::Bool(true)]);
+    }
+
+    DocumentBuilder::array()
+        .push(value)
+        .build()
+        .to_binary()
+        .unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let documents = [
+        ("small", small_document()),
+        ("wide", wide_document()),
+        ("deep", deep_document()),
+    ];
+
+    let mut group = c.benchmark_group("parse");
+
+    for (name, data) in documents.iter() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("owned", name), data, |b, data| {
+            b.iter(|| QJSONDocument::try_from(black_box(data.as_slice())).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), data, |b, data| {
+            b.iter(|| parse_borrowed(black_box(data.as_slice())).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use qt_json::elements::JsonValue;
+use qt_json::DocumentBuilder;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "document.qbjs".to_string());
+
+    let document = DocumentBuilder::object()
+        .insert("name", JsonValue::String("qt-json".to_string()))
+        .insert("version", JsonValue::Number(1.0))
+        .insert(
+            "tags",
+            JsonValue::Array(vec![
+                JsonValue::String("qt".to_string()),
+                JsonValue::String("json".to_string()),
+            ]),
+        )
+        .build();
+
+    let binary = document.to_binary().unwrap();
+
+    std::fs::write(&path, &binary).unwrap();
+
+    // Prints the number of bytes written
+    println!("Wrote {} bytes to {}", binary.len(), path);
+}
@@ -0,0 +1,12 @@
+use qt_json::QJSONDocument;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "document.dat".to_string());
+
+    let document = QJSONDocument::from_file(&path).unwrap();
+
+    // Prints the base of the document read from the file
+    println!("{:?}", document.base);
+}
@@ -0,0 +1,79 @@
+//! Prints a Qt binary JSON file as JSON text.
+//!
+//! ```sh
+//! cargo run --example qtjson-dump -- [--compact] [--stats] path/to/document.qbjs
+//! ```
+//!
+//! The output is pretty printed unless `--compact` is given. `--stats` prints the number of
+//! elements and values to stderr, so the JSON on stdout stays intact.
+
+use std::process::ExitCode;
+
+use qt_json::parse_with_stats;
+
+const USAGE: &str = "Usage: qtjson-dump [--compact] [--stats] <FILE>";
+
+fn main() -> ExitCode {
+    let mut compact = false;
+    let mut stats = false;
+    let mut path = None;
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--compact" => compact = true,
+            "--stats" => stats = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') || path.is_some() => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (document, document_stats) = match parse_with_stats(&data) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("Could not parse {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match compact {
+        true => println!("{}", document),
+        false => println!("{:#}", document),
+    }
+
+    if stats {
+        eprintln!(
+            "{} bytes, {} nodes: {} objects, {} arrays, {} strings, nested {} levels deep",
+            document_stats.bytes,
+            document_stats.nodes,
+            document_stats.objects,
+            document_stats.arrays,
+            document_stats.strings,
+            document_stats.max_depth
+        );
+    }
+
+    ExitCode::SUCCESS
+}
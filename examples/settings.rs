@@ -0,0 +1,38 @@
+//! Reads a Qt binary JSON file, e.g. a settings blob written by `QJsonDocument::toBinaryData`,
+//! and pretty prints its content.
+//!
+//! ```sh
+//! cargo run --example settings -- path/to/settings.qbjs
+//! ```
+
+use std::process::ExitCode;
+
+use qt_json::elements::JsonBaseValue;
+use qt_json::QJSONDocument;
+
+fn main() -> ExitCode {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: settings <FILE>");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let document = match QJSONDocument::from_file(&path) {
+        Ok(document) => document,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match document.base {
+        JsonBaseValue::Object(ref object) => println!("Object with {} entries:", object.size),
+        JsonBaseValue::Array(ref values) => println!("Array with {} values:", values.len()),
+    }
+
+    println!("{:#?}", document.base);
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qt_json::QJSONDocument;
+
+// Parsing arbitrary bytes must never panic. It may only succeed or return an error.
+fuzz_target!(|data: &[u8]| {
+    let _ = QJSONDocument::try_from(data);
+});
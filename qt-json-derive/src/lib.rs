@@ -0,0 +1,77 @@
+//! Derive macro for the `FromQtJson` trait of the `qt-json` crate.
+//!
+//! Use it through the `derive` feature of `qt-json` instead of depending on this crate
+//! directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// Implements `FromQtJson` for a struct with named fields.
+///
+/// Every field is read from the object entry with the same name. Missing entries are treated
+/// like `undefined`, so only `Option` fields may be missing.
+#[proc_macro_derive(FromQtJson)]
+pub fn derive_from_qt_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return syn::Error::new_spanned(
+                    &input.ident,
+                    "FromQtJson can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "FromQtJson can only be derived for structs",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let field_values = fields.iter().map(|field| {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("named fields have an identifier");
+        let key = ident.to_string();
+
+        quote! {
+            #ident: ::qt_json::__private::field(object, #key)?
+        }
+    });
+
+    let expanded = quote! {
+        impl #impl_generics ::qt_json::FromQtJson for #name #type_generics #where_clause {
+            fn from_qt_json(
+                value: &::qt_json::elements::JsonValue,
+            ) -> ::core::result::Result<Self, ::qt_json::QtJsonError> {
+                match value {
+                    ::qt_json::elements::JsonValue::Object(object) => Self::from_qt_object(object),
+                    other => ::core::result::Result::Err(::qt_json::__private::mismatch("object", other)),
+                }
+            }
+
+            fn from_qt_object(
+                object: &::qt_json::elements::Object,
+            ) -> ::core::result::Result<Self, ::qt_json::QtJsonError> {
+                ::core::result::Result::Ok(#name {
+                    #(#field_values,)*
+                })
+            }
+        }
+    };
+
+    expanded.into()
+}
@@ -0,0 +1,115 @@
+use qt_json::elements::JsonValue;
+use qt_json::{DocumentBuilder, FromQtJson, QJSONDocument, QtJsonError};
+
+#[derive(Debug, PartialEq, FromQtJson)]
+struct Test {
+    test: String,
+}
+
+#[derive(Debug, PartialEq, FromQtJson)]
+struct Config {
+    name: String,
+    port: u16,
+    ratio: f64,
+    enabled: bool,
+    comment: Option<String>,
+    tags: Vec<String>,
+    inner: Test,
+}
+
+#[test]
+fn deserialize_nested_fixture() {
+    let data = b"qbjs\x01\x00\x00\x00\x34\x00\x00\x00\x02\x00\x00\x00\x30\x00\x00\x00\x24\x00\
+                \x00\x00\x03\x00\x00\x00\x20\x00\x00\x00\x1B\x03\x00\x00\x04\x00test\x00\x00\x03\
+                \x00yes\x00\x00\x00\x0C\x00\x00\x00\x85\x01\x00\x00";
+
+    let tests: Vec<Test> = QJSONDocument::try_from(&data[..])
+        .unwrap()
+        .deserialize()
+        .unwrap();
+
+    assert_eq!(
+        tests,
+        vec![Test {
+            test: "yes".to_string()
+        }]
+    );
+}
+
+#[test]
+fn deserialize_struct() {
+    let inner = DocumentBuilder::object()
+        .insert("test", JsonValue::String("yes".to_string()))
+        .build();
+    let inner = match inner.base {
+        qt_json::elements::JsonBaseValue::Object(object) => JsonValue::Object(object),
+        _ => unreachable!(),
+    };
+
+    let document = DocumentBuilder::object()
+        .insert("name", JsonValue::String("server".to_string()))
+        .insert("port", JsonValue::Number(8080.0))
+        .insert("ratio", JsonValue::Number(0.5))
+        .insert("enabled", JsonValue::Bool(true))
+        .insert(
+            "tags",
+            JsonValue::Array(vec![
+                JsonValue::String("a".to_string()),
+                JsonValue::String("b".to_string()),
+            ]),
+        )
+        .insert("inner", inner)
+        .build();
+
+    let config: Config = document.deserialize().unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            name: "server".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            enabled: true,
+            comment: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+            inner: Test {
+                test: "yes".to_string()
+            },
+        }
+    );
+}
+
+#[test]
+fn deserialize_errors() {
+    let document = DocumentBuilder::object()
+        .insert("test", JsonValue::Number(1.0))
+        .build();
+
+    match document.deserialize::<Test>() {
+        Err(QtJsonError::UnexpectedType {
+            path,
+            expected,
+            found,
+        }) => {
+            assert_eq!(path, "test");
+            assert_eq!(expected, "string");
+            assert_eq!(found, "number");
+        }
+        other => panic!("Expected UnexpectedType, got {:?}", other),
+    }
+
+    let document = DocumentBuilder::object().build();
+    assert!(matches!(
+        document.deserialize::<Test>(),
+        Err(QtJsonError::UnexpectedType {
+            found: "undefined",
+            ..
+        })
+    ));
+
+    let document = DocumentBuilder::array().push(JsonValue::Bool(true)).build();
+    assert!(matches!(
+        document.deserialize::<Test>(),
+        Err(QtJsonError::UnexpectedType { found: "array", .. })
+    ));
+}
@@ -0,0 +1,134 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::{QJSONDocument, QT_JSON_TAG};
+
+/// Assembles a [`QJSONDocument`] programmatically.
+///
+/// ```rust
+/// use qt_json::DocumentBuilder;
+/// use qt_json::elements::JsonValue;
+///
+/// let document = DocumentBuilder::object()
+///     .insert("name", JsonValue::String("qt-json".to_string()))
+///     .insert("stars", JsonValue::Number(5.0))
+///     .build();
+///
+/// let binary = document.to_binary().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DocumentBuilder {
+    base: JsonBaseValue,
+}
+
+impl DocumentBuilder {
+    /// Creates a builder for a document with an Object as base
+    pub fn object() -> Self {
+        DocumentBuilder {
+            base: JsonBaseValue::Object(Object::default()),
+        }
+    }
+
+    /// Creates a builder for a document with an Array as base
+    pub fn array() -> Self {
+        DocumentBuilder {
+            base: JsonBaseValue::Array(Vec::new()),
+        }
+    }
+
+    /// Inserts a value into the base object. An existing value with the same key is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the builder has been created using [`DocumentBuilder::array`]
+    pub fn insert<K: Into<String>>(mut self, key: K, value: JsonValue) -> Self {
+        match self.base {
+            JsonBaseValue::Object(ref mut object) => {
+                object.insert(key, value);
+            }
+            JsonBaseValue::Array(_) => panic!("Cannot insert a key into an array document"),
+        }
+
+        self
+    }
+
+    /// Appends a value to the base array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the builder has been created using [`DocumentBuilder::object`]
+    pub fn push(mut self, value: JsonValue) -> Self {
+        match self.base {
+            JsonBaseValue::Array(ref mut values) => values.push(value),
+            JsonBaseValue::Object(_) => panic!("Cannot push a value into an object document"),
+        }
+
+        self
+    }
+
+    /// Finishes the document
+    pub fn build(self) -> QJSONDocument {
+        QJSONDocument {
+            tag: QT_JSON_TAG,
+            version: 1,
+            base: self.base,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elements::{JsonBaseValue, JsonValue};
+    use crate::{fixtures, DocumentBuilder, QJSONDocument};
+
+    #[test]
+    fn build_object() {
+        let document = DocumentBuilder::object()
+            .insert("test", JsonValue::String("yes".to_string()))
+            .build();
+
+        let binary = document.to_binary().unwrap();
+
+        assert_eq!(binary, fixtures::OBJECT_DOCUMENT.to_vec());
+    }
+
+    #[test]
+    fn build_array() {
+        let document = DocumentBuilder::array()
+            .push(JsonValue::Number(10.1))
+            .push(JsonValue::Bool(true))
+            .build();
+
+        let parsed = QJSONDocument::from_binary(document.to_binary().unwrap()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Array(ref vals) => {
+                assert_eq!(vals.len(), 2);
+                assert!(matches!(vals[0], JsonValue::Number(n) if n == 10.1));
+                assert!(matches!(vals[1], JsonValue::Bool(true)));
+            }
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    fn insert_keeps_size() {
+        let document = DocumentBuilder::object()
+            .insert("a", JsonValue::Null)
+            .insert("a", JsonValue::Bool(false))
+            .insert("b", JsonValue::Null)
+            .build();
+
+        match document.base {
+            JsonBaseValue::Object(ref object) => assert_eq!(object.size, 2),
+            _ => panic!("Expected object"),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_into_object() {
+        DocumentBuilder::object().push(JsonValue::Null);
+    }
+}
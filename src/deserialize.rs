@@ -0,0 +1,200 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::{QJSONDocument, QtJsonError};
+
+/// Converts a parsed value into a Rust type.
+///
+/// With the `derive` feature, this can be derived for structs with named fields. Every field
+/// is read from the object entry of the same name:
+///
+/// ```rust
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use qt_json::{FromQtJson, QJSONDocument};
+///
+/// #[derive(FromQtJson)]
+/// struct Config {
+///     test: String,
+///     missing: Option<f64>,
+/// }
+///
+/// let data = b"qbjs\x01\x00\x00\x00$\x00\x00\x00\x03\x00\x00\x00 \
+/// \x00\x00\x00\x1B\x03\x00\x00\x04\x00test\x00\x00\x03\x00yes\x00\x00\x00\x0C\x00\x00\x00";
+///
+/// let config: Config = QJSONDocument::try_from(&data[..]).unwrap().deserialize().unwrap();
+///
+/// assert_eq!(config.test, "yes");
+/// assert_eq!(config.missing, None);
+/// # }
+/// ```
+pub trait FromQtJson: Sized {
+    /// Converts the value. Missing object entries are passed as [`JsonValue::Undefined`].
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError>;
+
+    /// Converts the object at the base of a document.
+    ///
+    /// Types which are converted from objects should override this, the default implementation
+    /// has to copy the object.
+    fn from_qt_object(object: &Object) -> Result<Self, QtJsonError> {
+        Self::from_qt_json(&JsonValue::Object(object.clone()))
+    }
+
+    /// Converts the array at the base of a document.
+    ///
+    /// Types which are converted from arrays should override this, the default implementation
+    /// has to copy the array.
+    fn from_qt_array(values: &[JsonValue]) -> Result<Self, QtJsonError> {
+        Self::from_qt_json(&JsonValue::Array(values.to_vec()))
+    }
+}
+
+impl QJSONDocument {
+    /// Converts the base of the document into the given type
+    pub fn deserialize<T: FromQtJson>(&self) -> Result<T, QtJsonError> {
+        match self.base {
+            JsonBaseValue::Object(ref object) => T::from_qt_object(object),
+            JsonBaseValue::Array(ref values) => T::from_qt_array(values),
+        }
+    }
+}
+
+impl FromQtJson for JsonValue {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromQtJson for String {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        match value {
+            JsonValue::String(s) => Ok(s.clone()),
+            other => Err(mismatch("string", other)),
+        }
+    }
+}
+
+impl FromQtJson for bool {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        match value {
+            JsonValue::Bool(b) => Ok(*b),
+            other => Err(mismatch("bool", other)),
+        }
+    }
+}
+
+impl FromQtJson for f64 {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        match value {
+            JsonValue::Number(n) => Ok(*n),
+            other => Err(mismatch("number", other)),
+        }
+    }
+}
+
+impl FromQtJson for f32 {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        f64::from_qt_json(value).map(|n| n as f32)
+    }
+}
+
+/// Integers are only converted if the number is integral and within the range of the type
+macro_rules! impl_from_qt_json_int {
+    ($($int:ty => $via:ident),* $(,)?) => {
+        $(
+            impl FromQtJson for $int {
+                fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+                    value
+                        .$via()
+                        .and_then(|n| <$int>::try_from(n).ok())
+                        .ok_or_else(|| mismatch(stringify!($int), value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_qt_json_int!(
+    i8 => as_i64,
+    i16 => as_i64,
+    i32 => as_i64,
+    i64 => as_i64,
+    u8 => as_u64,
+    u16 => as_u64,
+    u32 => as_u64,
+    u64 => as_u64,
+);
+
+/// `undefined`, `null` and missing object entries are converted to `None`
+impl<T: FromQtJson> FromQtJson for Option<T> {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        match value {
+            JsonValue::Undefined | JsonValue::Null => Ok(None),
+            other => T::from_qt_json(other).map(Some),
+        }
+    }
+
+    fn from_qt_object(object: &Object) -> Result<Self, QtJsonError> {
+        T::from_qt_object(object).map(Some)
+    }
+
+    fn from_qt_array(values: &[JsonValue]) -> Result<Self, QtJsonError> {
+        T::from_qt_array(values).map(Some)
+    }
+}
+
+impl<T: FromQtJson> FromQtJson for Vec<T> {
+    fn from_qt_json(value: &JsonValue) -> Result<Self, QtJsonError> {
+        match value {
+            JsonValue::Array(values) => Self::from_qt_array(values),
+            other => Err(mismatch("array", other)),
+        }
+    }
+
+    fn from_qt_array(values: &[JsonValue]) -> Result<Self, QtJsonError> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                T::from_qt_json(value).map_err(|e| prefix(&format!("{}", index), e))
+            })
+            .collect()
+    }
+}
+
+/// Reads the entry of an object, used by the derive macro
+pub fn field<T: FromQtJson>(object: &Object, key: &str) -> Result<T, QtJsonError> {
+    let value = object.values.get(key).unwrap_or(&JsonValue::Undefined);
+
+    T::from_qt_json(value).map_err(|error| prefix(key, error))
+}
+
+/// Creates the error for a value of an unexpected type
+pub fn mismatch(expected: &'static str, found: &JsonValue) -> QtJsonError {
+    QtJsonError::UnexpectedType {
+        path: String::new(),
+        expected,
+        found: found.type_name(),
+    }
+}
+
+/// Prepends a segment to the path of an error
+fn prefix(segment: &str, error: QtJsonError) -> QtJsonError {
+    match error {
+        QtJsonError::UnexpectedType {
+            path,
+            expected,
+            found,
+        } => QtJsonError::UnexpectedType {
+            path: match path.is_empty() {
+                true => String::from(segment),
+                false => format!("{}.{}", segment, path),
+            },
+            expected,
+            found,
+        },
+        error => error,
+    }
+}
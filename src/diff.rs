@@ -0,0 +1,184 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::elements::{JsonValue, Object};
+use crate::flatten::pointer;
+
+/// A single difference found by [`diff`].
+///
+/// The path is a JSON pointer like the ones of
+/// [`QJSONDocument::to_btree`](crate::QJSONDocument::to_btree), the root value has an empty
+/// path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// The path only exists in the new value
+    Added { path: String, value: JsonValue },
+    /// The path only exists in the old value
+    Removed { path: String, value: JsonValue },
+    /// The path exists in both values, but the values differ
+    Modified {
+        path: String,
+        old: JsonValue,
+        new: JsonValue,
+    },
+}
+
+/// Returns every change needed to turn `a` into `b`.
+///
+/// Objects are compared key by key in sorted order and arrays are compared by index. A value
+/// which is not an object or array on both sides is reported as a single modification.
+pub fn diff(a: &JsonValue, b: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_value(&mut changes, "", a, b);
+
+    changes
+}
+
+fn diff_value(changes: &mut Vec<Change>, path: &str, a: &JsonValue, b: &JsonValue) {
+    match (a, b) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => diff_object(changes, path, a, b),
+        (JsonValue::Array(a), JsonValue::Array(b)) => diff_array(changes, path, a, b),
+        (a, b) if a != b => changes.push(Change::Modified {
+            path: String::from(path),
+            old: a.clone(),
+            new: b.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_object(changes: &mut Vec<Change>, path: &str, a: &Object, b: &Object) {
+    let mut keys: Vec<&String> = a.values.keys().chain(b.values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let path = pointer(path, key);
+
+        match (a.values.get(key), b.values.get(key)) {
+            (Some(a), Some(b)) => diff_value(changes, &path, a, b),
+            (Some(a), None) => changes.push(Change::Removed {
+                path,
+                value: a.clone(),
+            }),
+            (None, Some(b)) => changes.push(Change::Added {
+                path,
+                value: b.clone(),
+            }),
+            (None, None) => unreachable!("The key is taken from one of the objects"),
+        }
+    }
+}
+
+fn diff_array(changes: &mut Vec<Change>, path: &str, a: &[JsonValue], b: &[JsonValue]) {
+    for index in 0..a.len().max(b.len()) {
+        let path = format!("{}/{}", path, index);
+
+        match (a.get(index), b.get(index)) {
+            (Some(a), Some(b)) => diff_value(changes, &path, a, b),
+            (Some(a), None) => changes.push(Change::Removed {
+                path,
+                value: a.clone(),
+            }),
+            (None, Some(b)) => changes.push(Change::Added {
+                path,
+                value: b.clone(),
+            }),
+            (None, None) => unreachable!("The index is within one of the arrays"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::elements::{JsonBaseValue, JsonValue};
+    use crate::{diff, Change, DocumentBuilder};
+
+    fn base(builder: DocumentBuilder) -> JsonValue {
+        match builder.build().base {
+            JsonBaseValue::Object(object) => JsonValue::Object(object),
+            JsonBaseValue::Array(values) => JsonValue::Array(values),
+        }
+    }
+
+    #[test]
+    fn test_diff_objects() {
+        let a = base(
+            DocumentBuilder::object()
+                .insert("same", JsonValue::Bool(true))
+                .insert("changed", JsonValue::Number(1.0))
+                .insert("removed", JsonValue::Null)
+                .insert(
+                    "list",
+                    JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]),
+                ),
+        );
+        let b = base(
+            DocumentBuilder::object()
+                .insert("same", JsonValue::Bool(true))
+                .insert("changed", JsonValue::String("1".to_string()))
+                .insert("added", JsonValue::Bool(false))
+                .insert("list", JsonValue::Array(vec![JsonValue::Number(3.0)])),
+        );
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![
+                Change::Added {
+                    path: "/added".to_string(),
+                    value: JsonValue::Bool(false),
+                },
+                Change::Modified {
+                    path: "/changed".to_string(),
+                    old: JsonValue::Number(1.0),
+                    new: JsonValue::String("1".to_string()),
+                },
+                Change::Modified {
+                    path: "/list/0".to_string(),
+                    old: JsonValue::Number(1.0),
+                    new: JsonValue::Number(3.0),
+                },
+                Change::Removed {
+                    path: "/list/1".to_string(),
+                    value: JsonValue::Number(2.0),
+                },
+                Change::Removed {
+                    path: "/removed".to_string(),
+                    value: JsonValue::Null,
+                },
+            ]
+        );
+
+        assert!(diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_diff_root() {
+        assert_eq!(
+            diff(&JsonValue::Null, &JsonValue::Bool(true)),
+            vec![Change::Modified {
+                path: "".to_string(),
+                old: JsonValue::Null,
+                new: JsonValue::Bool(true),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_escaped_keys() {
+        let a = base(DocumentBuilder::object().insert("a.b/c", JsonValue::Null));
+        let b = base(DocumentBuilder::object());
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![Change::Removed {
+                path: "/a.b~1c".to_string(),
+                value: JsonValue::Null,
+            }]
+        );
+    }
+}
@@ -1,8 +1,46 @@
-use std::collections::HashMap;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::{Index, IndexMut, Range};
+
+use crate::{QJSONDocument, QTValueType, QtJsonError, UndefinedElementPolicy, QT_JSON_TAG};
+
+/// The map used to store the values of an [`Object`].
+///
+/// This is a `HashMap` when the `std` feature is enabled and a `BTreeMap` otherwise.
+#[cfg(feature = "std")]
+pub type Map<K, V> = std::collections::HashMap<K, V>;
+/// The map used to store the values of an [`Object`].
+///
+/// This is a `HashMap` when the `std` feature is enabled and a `BTreeMap` otherwise.
+#[cfg(not(feature = "std"))]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
 
 /// A JSON Value is the Enum containing a Value. This makes it easy to perform match operations
 /// against it.
-#[derive(Debug)]
+///
+/// New variants may be added without a breaking release, so a match outside of this crate
+/// needs a wildcard arm:
+///
+/// ```rust
+/// use qt_json::elements::JsonValue;
+///
+/// fn describe(value: &JsonValue) -> &'static str {
+///     match value {
+///         JsonValue::Object(_) | JsonValue::Array(_) => "container",
+///         JsonValue::String(_) | JsonValue::Number(_) | JsonValue::Bool(_) => "scalar",
+///         _ => "other",
+///     }
+/// }
+///
+/// assert_eq!(describe(&JsonValue::Null), "other");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum JsonValue {
     /// This encapsulates a RUST string.
     String(String),
@@ -20,23 +58,890 @@ pub enum JsonValue {
     Null,
 }
 
+impl JsonValue {
+    /// Creates an empty object
+    pub fn new_object() -> Self {
+        JsonValue::Object(Object::default())
+    }
+
+    /// Creates an empty array
+    pub fn new_array() -> Self {
+        JsonValue::Array(Vec::new())
+    }
+
+    /// Returns the type Qt uses to store the value in the binary format.
+    ///
+    /// Every number is a [`QTValueType::Double`], no matter if it has been stored inline as an
+    /// integer or as a 64 bit float.
+    pub fn qt_type(&self) -> QTValueType {
+        match self {
+            JsonValue::String(_) => QTValueType::String,
+            JsonValue::Number(_) => QTValueType::Double,
+            JsonValue::Object(_) => QTValueType::Object,
+            JsonValue::Array(_) => QTValueType::Array,
+            JsonValue::Undefined => QTValueType::Undefined,
+            JsonValue::Bool(_) => QTValueType::Bool,
+            JsonValue::Null => QTValueType::Null,
+        }
+    }
+
+    /// Returns the number, no matter if it is integral or not.
+    ///
+    /// Returns `None` for every non number value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            JsonValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an integer, see [`JsonValue::as_i64`].
+    ///
+    /// The binary format has no separate integer type, so this returns the integral numbers
+    /// stored inline as well as the integral numbers stored as a double. Integers larger than
+    /// `i64::MAX` are only returned by [`JsonValue::as_u64`].
+    pub fn as_integer(&self) -> Option<i64> {
+        self.as_i64()
+    }
+
+    /// Returns the name of the type of the value, e.g. for error messages.
+    ///
+    /// This is one of `"string"`, `"number"`, `"object"`, `"array"`, `"undefined"`, `"bool"`
+    /// and `"null"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::String(_) => "string",
+            JsonValue::Number(_) => "number",
+            JsonValue::Object(_) => "object",
+            JsonValue::Array(_) => "array",
+            JsonValue::Undefined => "undefined",
+            JsonValue::Bool(_) => "bool",
+            JsonValue::Null => "null",
+        }
+    }
+
+    /// Returns the number as an `i64` if it is integral and fits into an `i64`.
+    ///
+    /// Returns `None` for fractional numbers, numbers out of range and every non number value.
+    ///
+    /// Qt stores every integral number fitting into 27 bits inside of the value header, even
+    /// if it has been set as a double. Therefore `5` and `5.0` are the same number once
+    /// written, and both are returned by this function.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            JsonValue::Number(n) if n >= i64::MIN as f64 && n < i64::MAX as f64 => {
+                let i = n as i64;
+                (i as f64 == n).then_some(i)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `u64` if it is integral and fits into an `u64`.
+    ///
+    /// Returns `None` for fractional numbers, negative numbers, numbers out of range and every
+    /// non number value.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            JsonValue::Number(n) if n >= 0.0 && n < u64::MAX as f64 => {
+                let u = n as u64;
+                (u as f64 == n).then_some(u)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the object if the value is one
+    pub fn as_object(&self) -> Option<&Object> {
+        match self {
+            JsonValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns the object mutably if the value is one
+    pub fn as_object_mut(&mut self) -> Option<&mut Object> {
+        match self {
+            JsonValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns the values if the value is an array
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the values mutably if the value is an array
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the values if the value is an array, without cloning them
+    pub fn into_array(self) -> Option<Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of values of an array or the number of entries of an object.
+    ///
+    /// Returns `None` for every other value.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            JsonValue::Object(object) => Some(object.values.len()),
+            JsonValue::Array(values) => Some(values.len()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value is an empty array or an empty object.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Recursively collects every string value of objects and arrays.
+    ///
+    /// If `include_keys` is set, the keys of the objects are collected as well, each directly in
+    /// front of the strings of its value. The entries of an object are visited in the order of
+    /// their keys.
+    pub fn collect_strings(&self, include_keys: bool) -> Vec<&str> {
+        let mut strings = Vec::new();
+        self.collect_strings_into(include_keys, &mut strings);
+
+        strings
+    }
+
+    fn collect_strings_into<'a>(&'a self, include_keys: bool, strings: &mut Vec<&'a str>) {
+        match self {
+            JsonValue::String(s) => strings.push(s),
+            JsonValue::Object(object) => {
+                let mut entries: Vec<(&String, &JsonValue)> = object.values.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+
+                for (key, value) in entries {
+                    if include_keys {
+                        strings.push(key);
+                    }
+                    value.collect_strings_into(include_keys, strings);
+                }
+            }
+            JsonValue::Array(values) => {
+                for value in values {
+                    value.collect_strings_into(include_keys, strings);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Compares two values similar to the way JavaScript sorts them.
+    ///
+    /// - Numbers are compared numerically. `-0.0` equals `0.0` and `NaN` is greater than
+    ///   every other number.
+    /// - Strings are compared lexicographically by their UTF16 code units, like JavaScript does.
+    /// - `false` is less than `true`.
+    /// - Arrays are compared element-wise, a shorter array is less than a longer one starting
+    ///   with the same values.
+    /// - Objects are only compared by their number of entries.
+    ///
+    /// Values of different types are ordered by their type:
+    /// `Undefined < Null < Bool < Number < String < Array < Object`.
+    pub fn cmp_js(&self, other: &JsonValue) -> Ordering {
+        match (self, other) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => match a.partial_cmp(b) {
+                Some(ordering) => ordering,
+                None => a.is_nan().cmp(&b.is_nan()),
+            },
+            (JsonValue::String(a), JsonValue::String(b)) => a.encode_utf16().cmp(b.encode_utf16()),
+            (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match a.cmp_js(b) {
+                        Ordering::Equal => {}
+                        ordering => return ordering,
+                    }
+                }
+
+                a.len().cmp(&b.len())
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => a.values.len().cmp(&b.values.len()),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+
+    /// The position of the type in the ordering of [`JsonValue::cmp_js`]
+    fn type_rank(&self) -> u8 {
+        match self {
+            JsonValue::Undefined => 0,
+            JsonValue::Null => 1,
+            JsonValue::Bool(_) => 2,
+            JsonValue::Number(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+        }
+    }
+
+    /// Moves the value out and leaves [`JsonValue::Undefined`] in its place
+    pub fn take(&mut self) -> JsonValue {
+        core::mem::replace(self, JsonValue::Undefined)
+    }
+
+    /// Wraps an object or an array into a standalone document, which can be serialized using
+    /// [`QJSONDocument::to_binary`].
+    ///
+    /// Returns [`QtJsonError::InvalidBase`] for every other value.
+    pub fn into_document(self) -> Result<QJSONDocument, QtJsonError> {
+        let base = match self {
+            JsonValue::Object(object) => JsonBaseValue::Object(object),
+            JsonValue::Array(values) => JsonBaseValue::Array(values),
+            _ => return Err(QtJsonError::InvalidBase),
+        };
+
+        Ok(QJSONDocument {
+            tag: QT_JSON_TAG,
+            version: 1,
+            base,
+        })
+    }
+
+    /// Recursively shrinks the capacity of every string, array and object to fit its content
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            JsonValue::String(s) => s.shrink_to_fit(),
+            JsonValue::Object(object) => object.shrink_to_fit(),
+            JsonValue::Array(values) => {
+                values.iter_mut().for_each(JsonValue::shrink_to_fit);
+                values.shrink_to_fit();
+            }
+            JsonValue::Number(_) | JsonValue::Undefined | JsonValue::Bool(_) | JsonValue::Null => {}
+        }
+    }
+
+    /// Estimates the memory occupied by the value and everything nested in it, e.g. to limit
+    /// the size of a cache of parsed documents.
+    ///
+    /// The estimate covers the value itself and the capacity of every string, array and object.
+    /// The bookkeeping of the allocator and the internal overhead of the maps is not known, so
+    /// the actual usage is somewhat larger.
+    pub fn deep_size_bytes(&self) -> usize {
+        mem::size_of::<JsonValue>() + self.heap_size()
+    }
+
+    /// Returns the number of bytes allocated by the value, see [`JsonValue::deep_size_bytes`]
+    fn heap_size(&self) -> usize {
+        match self {
+            JsonValue::String(s) => s.capacity(),
+            JsonValue::Object(object) => object.heap_size(),
+            JsonValue::Array(values) => {
+                values.capacity() * mem::size_of::<JsonValue>()
+                    + values.iter().map(JsonValue::heap_size).sum::<usize>()
+            }
+            JsonValue::Number(_) | JsonValue::Undefined | JsonValue::Bool(_) | JsonValue::Null => 0,
+        }
+    }
+
+    /// Keeps only the values of an array for which the predicate returns true.
+    /// Every other value is left untouched.
+    pub fn retain_array<F: FnMut(&JsonValue) -> bool>(&mut self, f: F) {
+        if let JsonValue::Array(values) = self {
+            values.retain(f);
+        }
+    }
+
+    /// Applies the function to this value and then to every value nested in it. A value is
+    /// passed to the function before its children, so the children of a replaced value are the
+    /// new ones.
+    pub fn visit_mut<F: FnMut(&mut JsonValue)>(&mut self, mut f: F) {
+        self.visit_mut_with(&mut f);
+    }
+
+    fn visit_mut_with<F: FnMut(&mut JsonValue)>(&mut self, f: &mut F) {
+        f(self);
+
+        match self {
+            JsonValue::Object(object) => object
+                .values
+                .values_mut()
+                .for_each(|value| value.visit_mut_with(f)),
+            JsonValue::Array(values) => values.iter_mut().for_each(|value| value.visit_mut_with(f)),
+            JsonValue::String(_)
+            | JsonValue::Number(_)
+            | JsonValue::Undefined
+            | JsonValue::Bool(_)
+            | JsonValue::Null => {}
+        }
+    }
+
+    /// Recursively removes every entry of an object whose value is `undefined` and every
+    /// `undefined` element of an array. This cleans up a value before converting it into JSON,
+    /// which has no `undefined`.
+    pub fn prune_undefined(&mut self) {
+        self.prune_undefined_with(UndefinedElementPolicy::Remove);
+    }
+
+    /// Recursively removes every entry of an object whose value is `undefined` like
+    /// [`prune_undefined`](JsonValue::prune_undefined), but handles the `undefined` elements of
+    /// arrays according to the policy
+    pub fn prune_undefined_with(&mut self, elements: UndefinedElementPolicy) {
+        match self {
+            JsonValue::Object(object) => object.retain(|_, value| match value {
+                JsonValue::Undefined => false,
+                value => {
+                    value.prune_undefined_with(elements);
+                    true
+                }
+            }),
+            JsonValue::Array(values) => match elements {
+                UndefinedElementPolicy::Remove => values.retain_mut(|value| match value {
+                    JsonValue::Undefined => false,
+                    value => {
+                        value.prune_undefined_with(elements);
+                        true
+                    }
+                }),
+                UndefinedElementPolicy::Null => values.iter_mut().for_each(|value| match value {
+                    JsonValue::Undefined => *value = JsonValue::Null,
+                    value => value.prune_undefined_with(elements),
+                }),
+            },
+            JsonValue::String(_)
+            | JsonValue::Number(_)
+            | JsonValue::Undefined
+            | JsonValue::Bool(_)
+            | JsonValue::Null => {}
+        }
+    }
+
+    /// Deep merges another value into this one.
+    ///
+    /// If both values are objects, every entry of `other` is merged into the entry of the same
+    /// key, or inserted if this object does not contain the key. Every other combination,
+    /// including two arrays, replaces this value with `other`.
+    pub fn merge(&mut self, other: JsonValue) {
+        match (self, other) {
+            (JsonValue::Object(object), JsonValue::Object(other)) => {
+                for (key, value) in other.values {
+                    match object.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => {
+                            object.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Converts the value into an owned `serde_json::Value`.
+    ///
+    /// `Undefined` is mapped to `Null`, just like non finite numbers which can not be represented
+    /// in JSON. Numbers are always converted into floats.
+    #[cfg(feature = "serde")]
+    pub fn to_serde(&self) -> serde_json::Value {
+        match self {
+            JsonValue::String(s) => serde_json::Value::String(s.clone()),
+            JsonValue::Number(n) => serde_json::Number::from_f64(*n)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            JsonValue::Object(object) => serde_json::Value::Object(
+                object
+                    .values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_serde()))
+                    .collect(),
+            ),
+            JsonValue::Array(values) => {
+                serde_json::Value::Array(values.iter().map(JsonValue::to_serde).collect())
+            }
+            JsonValue::Undefined | JsonValue::Null => serde_json::Value::Null,
+            JsonValue::Bool(b) => serde_json::Value::Bool(*b),
+        }
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.into())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(n: f64) -> Self {
+        JsonValue::Number(n)
+    }
+}
+
+/// Numbers are stored as `f64`, so integers with an absolute value above 2^53 lose precision.
+impl From<i64> for JsonValue {
+    fn from(n: i64) -> Self {
+        JsonValue::Number(n as f64)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(values: Vec<JsonValue>) -> Self {
+        JsonValue::Array(values)
+    }
+}
+
+/// Takes the string out of the value. Every other value is returned unchanged as the error.
+impl TryFrom<JsonValue> for String {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, JsonValue> {
+        match value {
+            JsonValue::String(s) => Ok(s),
+            other => Err(other),
+        }
+    }
+}
+
+/// Takes the number out of the value. Every other value is returned unchanged as the error.
+impl TryFrom<JsonValue> for f64 {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, JsonValue> {
+        match value {
+            JsonValue::Number(n) => Ok(n),
+            other => Err(other),
+        }
+    }
+}
+
+/// Takes the bool out of the value. Every other value is returned unchanged as the error.
+impl TryFrom<JsonValue> for bool {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, JsonValue> {
+        match value {
+            JsonValue::Bool(b) => Ok(b),
+            other => Err(other),
+        }
+    }
+}
+
+/// Takes the object out of the value. Every other value is returned unchanged as the error.
+impl TryFrom<JsonValue> for Object {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, JsonValue> {
+        match value {
+            JsonValue::Object(object) => Ok(object),
+            other => Err(other),
+        }
+    }
+}
+
+/// Takes the values out of an array. Every other value is returned unchanged as the error.
+impl TryFrom<JsonValue> for Vec<JsonValue> {
+    type Error = JsonValue;
+
+    fn try_from(value: JsonValue) -> Result<Self, JsonValue> {
+        match value {
+            JsonValue::Array(values) => Ok(values),
+            other => Err(other),
+        }
+    }
+}
+
+/// Consumes an array and yields its values. Every other value yields nothing, use
+/// [`JsonValue::into_array`] to tell an empty array apart from those.
+impl IntoIterator for JsonValue {
+    type Item = JsonValue;
+    type IntoIter = alloc::vec::IntoIter<JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_array().unwrap_or_default().into_iter()
+    }
+}
+
+/// Returns the value of the given key of an object.
+///
+/// # Panics
+///
+/// Panics if the value is not an object or does not contain the key.
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        match self {
+            JsonValue::Object(object) => &object[key],
+            _ => panic!(
+                "Cannot index into a value of type {} using the key '{}'",
+                self.type_name(),
+                key
+            ),
+        }
+    }
+}
+
+/// Returns the value of the given key of an object mutably. A missing key is inserted as
+/// [`JsonValue::Null`] first, so a value can be assigned to it.
+///
+/// # Panics
+///
+/// Panics if the value is not an object.
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        match self {
+            JsonValue::Object(object) => &mut object[key],
+            _ => panic!(
+                "Cannot index into a value of type {} using the key '{}'",
+                self.type_name(),
+                key
+            ),
+        }
+    }
+}
+
+/// Returns the value at the given index of an array.
+///
+/// # Panics
+///
+/// Panics if the value is not an array or the index is out of bounds.
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        match self {
+            JsonValue::Array(values) => &values[index],
+            _ => panic!(
+                "Cannot index into a value of type {} using the index {}",
+                self.type_name(),
+                index
+            ),
+        }
+    }
+}
+
+/// Returns the value at the given index of an array mutably. Arrays are never extended.
+///
+/// # Panics
+///
+/// Panics if the value is not an array or the index is out of bounds.
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        match self {
+            JsonValue::Array(values) => &mut values[index],
+            _ => panic!(
+                "Cannot index into a value of type {} using the index {}",
+                self.type_name(),
+                index
+            ),
+        }
+    }
+}
+
+/// Numbers are compared using the `f64` equality. Therefore `NaN` is never equal to itself,
+/// even though `Eq` is implemented to allow using values as keys in maps and sets.
+impl Eq for JsonValue {}
+
+/// Hashes the variant together with its content. Numbers are hashed using their bit pattern,
+/// where `-0.0` and `0.0` as well as all `NaN`s share the same hash.
+///
+/// Since `NaN` is never equal to itself, a value containing a `NaN` can be inserted into a set
+/// multiple times.
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+
+        match self {
+            JsonValue::String(s) => s.hash(state),
+            JsonValue::Number(n) => number_bits(*n).hash(state),
+            JsonValue::Object(object) => object.hash(state),
+            JsonValue::Array(values) => values.hash(state),
+            JsonValue::Bool(b) => b.hash(state),
+            JsonValue::Undefined | JsonValue::Null => {}
+        }
+    }
+}
+
+/// Returns the bits of a number, unifying the representations of values which compare equal
+fn number_bits(n: f64) -> u64 {
+    if n == 0.0 {
+        0
+    } else if n.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        n.to_bits()
+    }
+}
+
 /// A JavaScript Object (i.e. A Map of keys and values where keys are strings)
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Object {
     /// The number of elements in the object
     pub size: u32,
     /// All extracted values from the Object. This does not include JavaScript specific values
     /// like prototypes and functions.
-    pub values: HashMap<String, JsonValue>,
+    pub values: Map<String, JsonValue>,
 }
 
-/// A spacial value which will be located at the base of a [`QJSONDocument`](struct.QJSONDocument.html)
+impl Object {
+    /// Returns a reference to the value of the given key
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.values.get(key)
+    }
+
+    /// Returns true if the object contains a value for the given key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// Returns an iterator over all keys of the object in an arbitrary order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Returns a mutable reference to the value of the given key
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.values.get_mut(key)
+    }
+
+    /// Inserts a value and returns the value previously stored under the key.
+    /// The size is updated accordingly.
+    pub fn insert<K: Into<String>>(&mut self, key: K, value: JsonValue) -> Option<JsonValue> {
+        let previous = self.values.insert(key.into(), value);
+        self.size = self.values.len() as u32;
+
+        previous
+    }
+
+    /// Keeps only the entries for which the predicate returns true. The size is updated
+    /// accordingly.
+    pub fn retain<F: FnMut(&str, &mut JsonValue) -> bool>(&mut self, mut f: F) {
+        self.values.retain(|key, value| f(key, value));
+        self.size = self.values.len() as u32;
+    }
+
+    /// Recursively shrinks the capacity of the map and every value to fit its content.
+    ///
+    /// The keys can not be modified while they are stored in the map, so their capacity is kept.
+    pub fn shrink_to_fit(&mut self) {
+        self.values.values_mut().for_each(JsonValue::shrink_to_fit);
+
+        // A BTreeMap allocates its nodes on demand and has no spare capacity
+        #[cfg(feature = "std")]
+        self.values.shrink_to_fit();
+    }
+
+    /// Estimates the memory occupied by the object and everything nested in it, see
+    /// [`JsonValue::deep_size_bytes`]
+    pub fn deep_size_bytes(&self) -> usize {
+        mem::size_of::<Object>() + self.heap_size()
+    }
+
+    /// Returns the number of bytes allocated by the object, see [`Object::deep_size_bytes`]
+    fn heap_size(&self) -> usize {
+        // A HashMap allocates its buckets up front and a control byte for each of them, a
+        // BTreeMap allocates its nodes on demand
+        #[cfg(feature = "std")]
+        let entries = self.values.capacity() * (mem::size_of::<(String, JsonValue)>() + 1);
+        #[cfg(not(feature = "std"))]
+        let entries = self.values.len() * mem::size_of::<(String, JsonValue)>();
+
+        entries
+            + self
+                .values
+                .iter()
+                .map(|(key, value)| key.capacity() + value.heap_size())
+                .sum::<usize>()
+    }
+
+    /// Returns the entry of the given key for an in place insertion or modification
+    pub fn entry<K: Into<String>>(&mut self, key: K) -> Entry<'_> {
+        Entry {
+            object: self,
+            key: key.into(),
+        }
+    }
+
+    /// Removes a key and returns its value. The size is updated accordingly.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let removed = self.values.remove(key);
+        self.size = self.values.len() as u32;
+
+        removed
+    }
+}
+
+/// An entry of an [`Object`] which may or may not contain a value, see [`Object::entry`].
+/// Inserting a value through the entry updates the size of the object.
 #[derive(Debug)]
+pub struct Entry<'a> {
+    object: &'a mut Object,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of the entry
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Inserts the default if the entry is empty and returns the value of the entry
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of the function if the entry is empty and returns the value of the
+    /// entry
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'a mut JsonValue {
+        let object = self.object;
+
+        if !object.values.contains_key(&self.key) {
+            object.size = object.values.len() as u32 + 1;
+        }
+
+        object.values.entry(self.key).or_insert_with(default)
+    }
+
+    /// Modifies the value if the entry contains one
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(self, f: F) -> Self {
+        if let Some(value) = self.object.values.get_mut(&self.key) {
+            f(value);
+        }
+
+        self
+    }
+}
+
+/// Collects the entries into an object. If a key occurs more than once, the last value is used
+/// and the size counts the key once.
+impl FromIterator<(String, JsonValue)> for Object {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(entries: I) -> Self {
+        let values: Map<String, JsonValue> = entries.into_iter().collect();
+
+        Object {
+            size: values.len() as u32,
+            values,
+        }
+    }
+}
+
+/// Returns the value of the given key.
+///
+/// # Panics
+///
+/// Panics if the object does not contain the key, use [`Object::get`] otherwise.
+impl Index<&str> for Object {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        match self.values.get(key) {
+            Some(value) => value,
+            None => panic!("The object does not contain the key '{}'", key),
+        }
+    }
+}
+
+/// Returns the value of the given key mutably. A missing key is inserted as
+/// [`JsonValue::Null`] first, so a value can be assigned to it. The size is updated
+/// accordingly.
+impl IndexMut<&str> for Object {
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        self.entry(key).or_insert(JsonValue::Null)
+    }
+}
+
+/// The entries are hashed in the order of their keys, so equal objects share the same hash
+/// independent of the iteration order of the map.
+impl Hash for Object {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut entries: Vec<(&String, &JsonValue)> = self.values.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        self.size.hash(state);
+        entries.hash(state);
+    }
+}
+
+/// A value borrowing its strings from the parsed data, see [`parse_borrowed`](crate::parse_borrowed).
+///
+/// Latin strings consisting of ASCII characters only are borrowed, every other string is
+/// decoded into an owned `String`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValueRef<'a> {
+    String(Cow<'a, str>),
+    Number(f64),
+    Object(Map<Cow<'a, str>, JsonValueRef<'a>>),
+    Array(Vec<JsonValueRef<'a>>),
+    Undefined,
+    Bool(bool),
+    Null,
+}
+
+impl JsonValueRef<'_> {
+    /// Copies every borrowed string and converts the value into a [`JsonValue`]
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            JsonValueRef::String(s) => JsonValue::String(s.into_owned()),
+            JsonValueRef::Number(n) => JsonValue::Number(n),
+            JsonValueRef::Object(values) => {
+                let values: Map<String, JsonValue> = values
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+
+                JsonValue::Object(Object {
+                    size: values.len() as u32,
+                    values,
+                })
+            }
+            JsonValueRef::Array(values) => {
+                JsonValue::Array(values.into_iter().map(JsonValueRef::into_owned).collect())
+            }
+            JsonValueRef::Undefined => JsonValue::Undefined,
+            JsonValueRef::Bool(b) => JsonValue::Bool(b),
+            JsonValueRef::Null => JsonValue::Null,
+        }
+    }
+}
+
+/// A spacial value which will be located at the base of a [`QJSONDocument`](struct.QJSONDocument.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JsonBaseValue {
     Object(Object),
     Array(Vec<JsonValue>),
 }
 
+impl JsonBaseValue {
+    /// Returns the number of entries of the object or the number of values of the array
+    pub fn len(&self) -> usize {
+        match self {
+            JsonBaseValue::Object(object) => object.values.len(),
+            JsonBaseValue::Array(values) => values.len(),
+        }
+    }
+
+    /// Returns true if the base does not contain any value
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /**
  * This is the base element of a JSON Document.
  *
@@ -58,3 +963,643 @@ pub struct JsonBase {
      */
     pub value: JsonBaseValue,
 }
+
+/// A value which is neither an object nor an array, like the leaves returned by
+/// [`QJSONDocument::to_btree`](crate::QJSONDocument::to_btree).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarValue {
+    String(String),
+    Number(f64),
+    Undefined,
+    Bool(bool),
+    Null,
+}
+
+/// How a string is stored in the binary format
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StringEncoding {
+    /// One byte per character. Only possible if every character is at most `U+00FF`.
+    Latin,
+    /// Two bytes per UTF16 code unit
+    Utf16,
+}
+
+impl StringEncoding {
+    pub(crate) fn from_latin(latin: bool) -> Self {
+        match latin {
+            true => StringEncoding::Latin,
+            false => StringEncoding::Utf16,
+        }
+    }
+}
+
+/// The encodings of the strings of a document, see
+/// [`QJSONDocument::from_binary_with_encodings`](crate::QJSONDocument::from_binary_with_encodings).
+///
+/// Both maps are keyed by the JSON pointer of the entry or value, like the ones returned by
+/// [`QJSONDocument::to_btree`](crate::QJSONDocument::to_btree). Strings without an encoding
+/// are stored as latin whenever possible.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StringEncodings {
+    /// The encodings of the keys of object entries
+    pub keys: BTreeMap<String, StringEncoding>,
+    /// The encodings of string values
+    pub values: BTreeMap<String, StringEncoding>,
+}
+
+/// The byte ranges of the values of a document, see
+/// [`QJSONDocument::from_binary_with_spans`](crate::QJSONDocument::from_binary_with_spans).
+///
+/// The ranges are keyed by the JSON pointer of the value, the base being stored at the empty
+/// pointer. They are relative to the start of the document, including its header.
+pub type Spans = BTreeMap<String, Range<usize>>;
+
+#[cfg(test)]
+mod test {
+    use core::cmp::Ordering;
+
+    use crate::elements::{JsonValue, Map, Object};
+    use crate::{fixtures, QJSONDocument, UndefinedElementPolicy};
+
+    #[test]
+    fn test_as_i64() {
+        assert_eq!(JsonValue::Number(10.0).as_i64(), Some(10));
+        assert_eq!(JsonValue::Number(-10.0).as_i64(), Some(-10));
+        assert_eq!(JsonValue::Number(10.1).as_i64(), None);
+        assert_eq!(
+            JsonValue::Number(5_000_000_000.0).as_i64(),
+            Some(5_000_000_000)
+        );
+        assert_eq!(JsonValue::Number(f64::NAN).as_i64(), None);
+        assert_eq!(JsonValue::Number(1e300).as_i64(), None);
+        assert_eq!(JsonValue::Bool(true).as_i64(), None);
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(JsonValue::String("5".to_string()).type_name(), "string");
+        assert_eq!(JsonValue::Number(5.0).type_name(), "number");
+        assert_eq!(JsonValue::new_object().type_name(), "object");
+        assert_eq!(JsonValue::new_array().type_name(), "array");
+        assert_eq!(JsonValue::Undefined.type_name(), "undefined");
+        assert_eq!(JsonValue::Bool(true).type_name(), "bool");
+        assert_eq!(JsonValue::Null.type_name(), "null");
+    }
+
+    #[test]
+    fn test_as_f64() {
+        assert_eq!(JsonValue::Number(5.0).as_f64(), Some(5.0));
+        assert_eq!(JsonValue::Number(5.5).as_f64(), Some(5.5));
+        assert_eq!(JsonValue::String("5".to_string()).as_f64(), None);
+
+        // [5, 5.5] with the 5 stored inline and the 5.5 stored as a double
+        let data = b"qbjs\x01\x00\x00\x00\x1C\x00\x00\x00\x04\x00\x00\x00\x14\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\x16\x40\xAA\x00\x00\x00\x82\x01\x00\x00";
+        let document = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        let values = document.base_as_array().unwrap();
+
+        assert_eq!(values[0].as_f64(), Some(5.0));
+        assert_eq!(values[0].as_i64(), Some(5));
+        assert_eq!(values[1].as_f64(), Some(5.5));
+        assert_eq!(values[1].as_i64(), None);
+    }
+
+    #[test]
+    fn test_as_integer() {
+        assert_eq!(JsonValue::Number(-5.0).as_integer(), Some(-5));
+        assert_eq!(JsonValue::Number(5.5).as_integer(), None);
+        assert_eq!(JsonValue::Number(u64::MAX as f64 / 2.0).as_integer(), None);
+        assert_eq!(JsonValue::Bool(true).as_integer(), None);
+
+        // The writer stores the 5 inline like Qt and the others as a double
+        let document = crate::DocumentBuilder::array()
+            .push(JsonValue::Number(5.0))
+            .push(JsonValue::Number(5.5))
+            .push(JsonValue::Number((1u64 << 40) as f64))
+            .build();
+        let document = QJSONDocument::from_binary(document.to_binary().unwrap()).unwrap();
+        let values = document.base_as_array().unwrap();
+
+        assert_eq!(values[0].as_integer(), Some(5));
+        assert_eq!(values[1].as_integer(), None);
+        assert_eq!(values[2].as_integer(), Some(1 << 40));
+    }
+
+    #[test]
+    fn test_as_u64() {
+        assert_eq!(JsonValue::Number(10.0).as_u64(), Some(10));
+        assert_eq!(JsonValue::Number(10.1).as_u64(), None);
+        assert_eq!(JsonValue::Number(-1.0).as_u64(), None);
+        assert_eq!(
+            JsonValue::Number(u32::MAX as f64 + 1.0).as_u64(),
+            Some(u32::MAX as u64 + 1)
+        );
+        assert_eq!(JsonValue::Number(1e300).as_u64(), None);
+        assert_eq!(JsonValue::Null.as_u64(), None);
+    }
+
+    #[test]
+    fn test_default_object() {
+        let mut object = Object::default();
+        assert_eq!(object.size, 0);
+        assert!(object.values.is_empty());
+
+        object.insert("test", JsonValue::new_array());
+        assert_eq!(object.size, 1);
+        assert_eq!(object.get("test"), Some(&JsonValue::Array(vec![])));
+
+        assert_eq!(
+            JsonValue::new_object(),
+            JsonValue::Object(Object::default())
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut object = Object::default();
+
+        let value = object.entry("count").or_insert(JsonValue::Null);
+        assert_eq!(*value, JsonValue::Null);
+        *value = JsonValue::Number(1.0);
+        assert_eq!(object.size, 1);
+
+        object
+            .entry("count")
+            .and_modify(|value| *value = JsonValue::Number(2.0))
+            .or_insert(JsonValue::Null);
+        assert_eq!(object.get("count"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(object.size, 1);
+
+        let entry = object.entry("other");
+        assert_eq!(entry.key(), "other");
+        entry.or_insert_with(JsonValue::new_array);
+        assert_eq!(object.get("other"), Some(&JsonValue::Array(vec![])));
+        assert_eq!(object.size, 2);
+    }
+
+    #[test]
+    fn test_object_mutation() {
+        let mut values = Map::new();
+        values.insert("test".to_string(), JsonValue::String("yes".to_string()));
+        let mut object = Object { size: 1, values };
+
+        match object.get_mut("test") {
+            Some(JsonValue::String(s)) => s.push('!'),
+            _ => panic!("Expected string"),
+        }
+        assert!(matches!(object.values.get("test"), Some(JsonValue::String(s)) if s == "yes!"));
+        assert_eq!(object.size, 1);
+
+        assert!(object.insert("test", JsonValue::Null).is_some());
+        assert_eq!(object.size, 1);
+
+        assert!(object.insert("other", JsonValue::Bool(true)).is_none());
+        assert_eq!(object.size, 2);
+
+        assert!(matches!(object.remove("test"), Some(JsonValue::Null)));
+        assert_eq!(object.size, 1);
+
+        assert!(object.remove("test").is_none());
+        assert_eq!(object.size, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_to_serde() {
+        let value = fixtures::nested_array();
+
+        assert_eq!(value.to_serde(), serde_json::json!([{ "test": "yes" }]));
+
+        let scalars = JsonValue::Array(vec![
+            JsonValue::Undefined,
+            JsonValue::Null,
+            JsonValue::Bool(false),
+            JsonValue::Number(1.5),
+            JsonValue::Number(f64::NAN),
+        ]);
+
+        assert_eq!(
+            scalars.to_serde(),
+            serde_json::json!([null, null, false, 1.5, null])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hash_set() {
+        use crate::elements::JsonBaseValue;
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+
+        for _ in 0..2 {
+            match fixtures::nested_document().base {
+                JsonBaseValue::Array(mut values) => set.insert(values.remove(0)),
+                _ => panic!("Expected array"),
+            };
+        }
+
+        assert_eq!(set.len(), 1);
+
+        set.insert(JsonValue::Number(0.0));
+        set.insert(JsonValue::Number(-0.0));
+        set.insert(JsonValue::Null);
+        set.insert(JsonValue::Undefined);
+
+        assert_eq!(set.len(), 4);
+    }
+
+    #[test]
+    fn test_equality() {
+        assert_eq!(JsonValue::Number(1.0), JsonValue::Number(1.0));
+        assert_ne!(JsonValue::Number(f64::NAN), JsonValue::Number(f64::NAN));
+        assert_ne!(JsonValue::Null, JsonValue::Undefined);
+        assert_ne!(JsonValue::String("1".to_string()), JsonValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_len() {
+        let array = fixtures::nested_array();
+
+        assert_eq!(array.len(), Some(1));
+        assert!(!array.is_empty());
+
+        match array {
+            JsonValue::Array(ref values) => {
+                assert_eq!(values[0].len(), Some(1));
+                assert!(!values[0].is_empty());
+            }
+            _ => panic!("Expected array"),
+        }
+
+        assert_eq!(JsonValue::Array(vec![]).len(), Some(0));
+        assert!(JsonValue::Array(vec![]).is_empty());
+        assert_eq!(JsonValue::String("yes".to_string()).len(), None);
+        assert!(!JsonValue::Null.is_empty());
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(JsonValue::from("hi"), JsonValue::String("hi".to_string()));
+        assert_eq!(
+            JsonValue::from("hi".to_string()),
+            JsonValue::String("hi".to_string())
+        );
+        assert_eq!(JsonValue::from(1.5), JsonValue::Number(1.5));
+        assert_eq!(JsonValue::from(-3i64), JsonValue::Number(-3.0));
+        assert_eq!(JsonValue::from(true), JsonValue::Bool(true));
+
+        let array: JsonValue = vec![JsonValue::Null, 2.0.into()].into();
+        assert_eq!(
+            array,
+            JsonValue::Array(vec![JsonValue::Null, JsonValue::Number(2.0)])
+        );
+    }
+
+    #[test]
+    fn test_collect_strings() {
+        let nested = fixtures::nested_array();
+
+        assert_eq!(nested.collect_strings(false), vec!["yes"]);
+        assert_eq!(nested.collect_strings(true), vec!["test", "yes"]);
+        assert!(JsonValue::Null.collect_strings(true).is_empty());
+    }
+
+    #[test]
+    fn test_cmp_js() {
+        let mut values = [
+            JsonValue::String("b".to_string()),
+            JsonValue::Number(f64::NAN),
+            JsonValue::Object(Object {
+                size: 0,
+                values: Map::new(),
+            }),
+            JsonValue::Number(10.0),
+            JsonValue::Array(vec![JsonValue::Null]),
+            JsonValue::Bool(true),
+            JsonValue::String("a".to_string()),
+            JsonValue::Null,
+            JsonValue::Number(-2.5),
+            JsonValue::Array(vec![]),
+            JsonValue::Bool(false),
+            JsonValue::Undefined,
+        ];
+
+        values.sort_by(|a, b| a.cmp_js(b));
+
+        assert_eq!(values[0], JsonValue::Undefined);
+        assert_eq!(values[1], JsonValue::Null);
+        assert_eq!(values[2], JsonValue::Bool(false));
+        assert_eq!(values[3], JsonValue::Bool(true));
+        assert_eq!(values[4], JsonValue::Number(-2.5));
+        assert_eq!(values[5], JsonValue::Number(10.0));
+        assert!(matches!(values[6], JsonValue::Number(n) if n.is_nan()));
+        assert_eq!(values[7], JsonValue::String("a".to_string()));
+        assert_eq!(values[8], JsonValue::String("b".to_string()));
+        assert_eq!(values[9], JsonValue::Array(vec![]));
+        assert_eq!(values[10], JsonValue::Array(vec![JsonValue::Null]));
+        assert!(matches!(values[11], JsonValue::Object(_)));
+
+        assert_eq!(
+            JsonValue::Number(-0.0).cmp_js(&JsonValue::Number(0.0)),
+            Ordering::Equal
+        );
+        // U+FF61 is a single code unit, while U+1F600 is encoded as a surrogate pair
+        assert_eq!(
+            JsonValue::String("\u{FF61}".to_string())
+                .cmp_js(&JsonValue::String("\u{1F600}".to_string())),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_retain_array() {
+        let mut array = JsonValue::Array(vec![
+            JsonValue::Number(1.0),
+            JsonValue::Null,
+            JsonValue::Number(2.0),
+        ]);
+        array.retain_array(|value| !matches!(value, JsonValue::Null));
+
+        assert_eq!(
+            array,
+            JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)])
+        );
+
+        let mut string = JsonValue::String("test".to_string());
+        string.retain_array(|_| false);
+
+        assert_eq!(string, JsonValue::String("test".to_string()));
+    }
+
+    #[test]
+    fn test_prune_undefined() {
+        let mut object = Object::default();
+        object.insert("gone", JsonValue::Undefined);
+        object.insert(
+            "list",
+            JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Undefined,
+                JsonValue::Array(vec![JsonValue::Undefined]),
+            ]),
+        );
+        let mut inner = Object::default();
+        inner.insert("gone", JsonValue::Undefined);
+        inner.insert("kept", JsonValue::Null);
+        object.insert("inner", JsonValue::Object(inner));
+        let value = JsonValue::Object(object);
+
+        let mut removed = value.clone();
+        removed.prune_undefined();
+
+        let mut expected = Object::default();
+        expected.insert(
+            "list",
+            JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::new_array()]),
+        );
+        let mut inner = Object::default();
+        inner.insert("kept", JsonValue::Null);
+        expected.insert("inner", JsonValue::Object(inner.clone()));
+        assert_eq!(removed, JsonValue::Object(expected.clone()));
+        assert_eq!(removed.as_object().unwrap().size, 2);
+
+        let mut nulled = value;
+        nulled.prune_undefined_with(UndefinedElementPolicy::Null);
+
+        expected.insert(
+            "list",
+            JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Null,
+                JsonValue::Array(vec![JsonValue::Null]),
+            ]),
+        );
+        assert_eq!(nulled, JsonValue::Object(expected));
+    }
+
+    #[test]
+    fn test_visit_mut() {
+        let mut value = fixtures::nested_array();
+
+        let mut visited = 0;
+        value.visit_mut(|value| {
+            visited += 1;
+            if let JsonValue::String(s) = value {
+                *s = s.to_uppercase();
+            }
+        });
+
+        let mut object = Object::default();
+        object.insert("test", JsonValue::String("YES".to_string()));
+        assert_eq!(value, JsonValue::Array(vec![JsonValue::Object(object)]));
+        assert_eq!(visited, 3);
+    }
+
+    #[test]
+    fn test_deep_size_bytes() {
+        let value_size = core::mem::size_of::<JsonValue>();
+        assert_eq!(JsonValue::Null.deep_size_bytes(), value_size);
+        assert_eq!(
+            JsonValue::String(String::with_capacity(10)).deep_size_bytes(),
+            value_size + 10
+        );
+
+        // Wrapping the object in an array adds the buffer of the array and the value holding it
+        let object = fixtures::object();
+        let nested = fixtures::nested_array();
+        let capacity = match &nested {
+            JsonValue::Array(values) => values.capacity(),
+            _ => panic!("Expected array"),
+        };
+        assert_eq!(
+            nested.deep_size_bytes() - object.deep_size_bytes(),
+            (capacity + 1) * value_size - core::mem::size_of::<Object>()
+        );
+
+        // Replacing a string only changes the size by the difference of the capacities
+        let mut larger = fixtures::object();
+        larger.insert("test", JsonValue::String(String::with_capacity(64)));
+        let previous = match object.get("test") {
+            Some(JsonValue::String(s)) => s.capacity(),
+            _ => panic!("Expected string"),
+        };
+        assert_eq!(
+            larger.deep_size_bytes() - object.deep_size_bytes(),
+            64 - previous
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(
+            String::try_from(JsonValue::String("yes".to_string())),
+            Ok("yes".to_string())
+        );
+        assert_eq!(f64::try_from(JsonValue::Number(2.5)), Ok(2.5));
+        assert_eq!(bool::try_from(JsonValue::Bool(true)), Ok(true));
+        assert_eq!(
+            Object::try_from(JsonValue::new_object()),
+            Ok(Object::default())
+        );
+        assert_eq!(
+            Vec::<JsonValue>::try_from(JsonValue::Array(vec![JsonValue::Null])),
+            Ok(vec![JsonValue::Null])
+        );
+
+        // A mismatch returns the original value
+        assert_eq!(
+            String::try_from(JsonValue::Number(2.5)),
+            Err(JsonValue::Number(2.5))
+        );
+        assert_eq!(
+            f64::try_from(JsonValue::String("2.5".to_string())),
+            Err(JsonValue::String("2.5".to_string()))
+        );
+        assert_eq!(bool::try_from(JsonValue::Null), Err(JsonValue::Null));
+        assert_eq!(
+            Object::try_from(JsonValue::new_array()),
+            Err(JsonValue::new_array())
+        );
+        let result: Result<Vec<JsonValue>, _> = JsonValue::new_object().try_into();
+        assert_eq!(result, Err(JsonValue::new_object()));
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut value = JsonValue::new_object();
+        value["key"] = JsonValue::from(5.0);
+        value["nested"] = JsonValue::new_object();
+        value["nested"]["list"] = JsonValue::from(vec![JsonValue::Null, JsonValue::Null]);
+        value["nested"]["list"][1] = JsonValue::from("set");
+
+        assert_eq!(value["key"], JsonValue::Number(5.0));
+        assert_eq!(value["nested"]["list"][0], JsonValue::Null);
+        assert_eq!(
+            value["nested"]["list"][1],
+            JsonValue::String("set".to_string())
+        );
+
+        let object = value.as_object_mut().unwrap();
+        assert_eq!(object.size, 2);
+        object["key"] = JsonValue::Bool(true);
+        assert_eq!(object["key"], JsonValue::Bool(true));
+        assert_eq!(object.size, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_missing_key() {
+        let _ = &Object::default()["missing"];
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let mut value = JsonValue::new_array();
+        value[0] = JsonValue::Null;
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let array = fixtures::nested_array();
+
+        let objects: Vec<Object> = array
+            .into_iter()
+            .filter_map(|value| match value {
+                JsonValue::Object(object) => Some(object),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(
+            objects[0].get("test"),
+            Some(&JsonValue::String("yes".to_string()))
+        );
+
+        assert_eq!(JsonValue::Null.into_iter().count(), 0);
+        assert_eq!(JsonValue::Null.into_array(), None);
+        assert_eq!(JsonValue::new_array().into_array(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_collect_object() {
+        let object: Object = vec![
+            ("a".to_string(), JsonValue::Number(1.0)),
+            ("b".to_string(), JsonValue::Bool(true)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(object.size, 2);
+        assert_eq!(object.get("a"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(object.get("b"), Some(&JsonValue::Bool(true)));
+    }
+
+    #[test]
+    fn test_take() {
+        let mut array = JsonValue::Array(vec![
+            JsonValue::String("test".to_string()),
+            JsonValue::Bool(true),
+        ]);
+
+        let taken = array.as_array_mut().unwrap()[0].take();
+
+        assert_eq!(taken, JsonValue::String("test".to_string()));
+        assert_eq!(
+            array,
+            JsonValue::Array(vec![JsonValue::Undefined, JsonValue::Bool(true)])
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let object = |entries: Vec<(&str, JsonValue)>| {
+            let mut object = Object {
+                size: 0,
+                values: Map::new(),
+            };
+            for (key, value) in entries {
+                object.insert(key, value);
+            }
+            JsonValue::Object(object)
+        };
+
+        let mut base = object(vec![("a", JsonValue::Number(1.0))]);
+        base.merge(object(vec![("b", JsonValue::Number(2.0))]));
+
+        assert_eq!(
+            base,
+            object(vec![
+                ("a", JsonValue::Number(1.0)),
+                ("b", JsonValue::Number(2.0))
+            ])
+        );
+
+        let mut base = object(vec![
+            ("a", JsonValue::Number(1.0)),
+            ("nested", object(vec![("x", JsonValue::Bool(true))])),
+            ("list", JsonValue::Array(vec![JsonValue::Null])),
+        ]);
+        base.merge(object(vec![
+            ("a", JsonValue::Number(3.0)),
+            ("nested", object(vec![("y", JsonValue::Bool(false))])),
+            ("list", JsonValue::Array(vec![])),
+        ]));
+
+        assert_eq!(
+            base,
+            object(vec![
+                ("a", JsonValue::Number(3.0)),
+                (
+                    "nested",
+                    object(vec![
+                        ("x", JsonValue::Bool(true)),
+                        ("y", JsonValue::Bool(false))
+                    ])
+                ),
+                ("list", JsonValue::Array(vec![])),
+            ])
+        );
+    }
+}
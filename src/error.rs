@@ -0,0 +1,136 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+/// Every error which can occur while parsing a Qt binary JSON document.
+#[derive(Debug)]
+pub enum QtJsonError {
+    /// The document does not start with the "qbjs" tag. Contains the tag which has been found.
+    InvalidTag(u32),
+    /// The document has been written with a QBJS version this library does not understand.
+    UnsupportedVersion(u32),
+    /// An offset or length points outside of the provided data
+    OutOfBounds,
+    /// A string is not valid UTF16, e.g. because it contains a lone surrogate.
+    ///
+    /// The position is the index of the first invalid code unit in the stored string,
+    /// counting a leading byte order mark of the opposite byte order. The path is the JSON
+    /// pointer of the value like the ones of `to_btree`. For an invalid key it is the pointer
+    /// of the object containing the key, which is empty for the base.
+    InvalidUtf16 { path: String, position: usize },
+    /// A value header contains a type which is not known
+    UnknownValueType(u32),
+    /// The base of the document is neither an Array nor an Object.
+    ///
+    /// The base of a binary document is always read as an element, whose header can only mark
+    /// it as an object or an array. Therefore parsing never finds a scalar base, this is only
+    /// returned when a scalar is converted into a document.
+    InvalidBase,
+    /// The data is malformed in a way not covered by the other variants
+    InvalidData(String),
+    /// The document is too large to be represented in the Qt binary JSON format
+    DocumentTooLarge,
+    /// The serialized document would be larger than the configured maximum output size
+    OutputSizeExceeded(usize),
+    /// The objects and arrays are nested deeper than the configured maximum depth
+    DepthLimitExceeded(usize),
+    /// A key or a string value is longer than the configured maximum string length
+    StringLimitExceeded(usize),
+    /// A number is NaN or infinite and [`NonFinitePolicy::Error`](crate::NonFinitePolicy::Error)
+    /// is used
+    NonFiniteNumber(f64),
+    /// A value could not be converted into a Rust type, because it has a different type.
+    /// The path is dotted like the one of `into_flat` and empty for the base.
+    UnexpectedType {
+        path: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// Reading the document from the file system failed
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The text passed to [`QJSONDocument::from_base64`](crate::QJSONDocument::from_base64) is
+    /// not valid base64
+    #[cfg(feature = "base64")]
+    InvalidBase64(base64::DecodeError),
+}
+
+impl Display for QtJsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            QtJsonError::InvalidTag(tag) => write!(f, "Invalid document tag: {:#010X}", tag),
+            QtJsonError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported QBJS version: {}", version)
+            }
+            QtJsonError::OutOfBounds => write!(f, "Data access out of bounds"),
+            QtJsonError::InvalidUtf16 { path, position } => write!(
+                f,
+                "Invalid UTF16 at code unit {} of the string at '{}'",
+                position, path
+            ),
+            QtJsonError::UnknownValueType(value_type) => {
+                write!(f, "Unknown value type: {:#0X}", value_type)
+            }
+            QtJsonError::InvalidBase => write!(f, "The Base must be either an Array or object"),
+            QtJsonError::InvalidData(message) => write!(f, "{}", message),
+            QtJsonError::DocumentTooLarge => write!(f, "The document is too large"),
+            QtJsonError::OutputSizeExceeded(limit) => {
+                write!(f, "The serialized document exceeds {} bytes", limit)
+            }
+            QtJsonError::DepthLimitExceeded(depth) => {
+                write!(f, "The document is nested deeper than {} levels", depth)
+            }
+            QtJsonError::StringLimitExceeded(len) => {
+                write!(f, "A string is longer than {} characters", len)
+            }
+            QtJsonError::NonFiniteNumber(n) => write!(f, "Non finite number: {}", n),
+            QtJsonError::UnexpectedType {
+                path,
+                expected,
+                found,
+            } => write!(f, "Expected {} at '{}', found {}", expected, path, found),
+            #[cfg(feature = "std")]
+            QtJsonError::Io(error) => write!(f, "IO error: {}", error),
+            #[cfg(feature = "base64")]
+            QtJsonError::InvalidBase64(error) => write!(f, "Invalid base64: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QtJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QtJsonError::Io(error) => Some(error),
+            #[cfg(feature = "base64")]
+            QtJsonError::InvalidBase64(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for QtJsonError {
+    fn from(error: std::io::Error) -> Self {
+        QtJsonError::Io(error)
+    }
+}
+
+#[cfg(feature = "base64")]
+impl From<base64::DecodeError> for QtJsonError {
+    fn from(error: base64::DecodeError) -> Self {
+        QtJsonError::InvalidBase64(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<QtJsonError> for std::io::Error {
+    fn from(error: QtJsonError) -> Self {
+        let kind = match error {
+            QtJsonError::Io(error) => return error,
+            QtJsonError::OutOfBounds => std::io::ErrorKind::UnexpectedEof,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+
+        std::io::Error::new(kind, error)
+    }
+}
@@ -0,0 +1,37 @@
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::QJSONDocument;
+
+/// `{"test": "yes"}` as written by Qt
+pub(crate) const OBJECT_DOCUMENT: &[u8] = b"qbjs\x01\x00\x00\x00$\x00\x00\x00\x03\x00\x00\x00 \
+        \x00\x00\x00\x1B\x03\x00\x00\x04\x00test\x00\x00\x03\x00yes\x00\x00\x00\x0C\x00\x00\x00";
+
+/// `[{"test": "yes"}]` as written by Qt
+pub(crate) const NESTED_DOCUMENT: &[u8] = b"qbjs\x01\x00\x00\x00\x34\x00\x00\x00\x02\x00\x00\x00\
+        \x30\x00\x00\x00\x24\x00\x00\x00\x03\x00\x00\x00\x20\x00\x00\x00\x1B\x03\x00\x00\x04\x00\
+        test\x00\x00\x03\x00yes\x00\x00\x00\x0C\x00\x00\x00\x85\x01\x00\x00";
+
+/// Parses [`OBJECT_DOCUMENT`]
+pub(crate) fn object_document() -> QJSONDocument {
+    QJSONDocument::from_binary(OBJECT_DOCUMENT.to_vec()).unwrap()
+}
+
+/// Parses [`NESTED_DOCUMENT`]
+pub(crate) fn nested_document() -> QJSONDocument {
+    QJSONDocument::from_binary(NESTED_DOCUMENT.to_vec()).unwrap()
+}
+
+/// Returns the base of [`NESTED_DOCUMENT`] as a value
+pub(crate) fn nested_array() -> JsonValue {
+    match nested_document().base {
+        JsonBaseValue::Array(values) => JsonValue::Array(values),
+        JsonBaseValue::Object(_) => panic!("Expected array"),
+    }
+}
+
+/// Returns the base of [`OBJECT_DOCUMENT`] as an object
+pub(crate) fn object() -> Object {
+    match object_document().base {
+        JsonBaseValue::Object(object) => object,
+        JsonBaseValue::Array(_) => panic!("Expected object"),
+    }
+}
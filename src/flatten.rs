@@ -0,0 +1,177 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object, ScalarValue};
+use crate::QJSONDocument;
+
+impl QJSONDocument {
+    /// Consumes the document and returns every scalar value together with its dotted path.
+    ///
+    /// Object keys and array indices are joined using a `.` (e.g. `users.0.name`). The keys of
+    /// an object are visited in sorted order so the result is deterministic. Empty objects and
+    /// arrays have no scalars and therefore do not show up in the result.
+    pub fn into_flat(self) -> Vec<(String, JsonValue)> {
+        let mut flat = Vec::new();
+
+        match self.base {
+            JsonBaseValue::Object(object) => flatten_object(&mut flat, None, object),
+            JsonBaseValue::Array(values) => flatten_array(&mut flat, None, values),
+        }
+
+        flat
+    }
+
+    /// Returns every scalar value keyed by its JSON pointer (RFC 6901, e.g. `/users/0/name`).
+    ///
+    /// Unlike [`into_flat`](QJSONDocument::into_flat) the document is kept and the map keeps the
+    /// pointers sorted, which makes the result suitable for snapshots and diffs. Empty objects
+    /// and arrays do not show up in the result.
+    pub fn to_btree(&self) -> BTreeMap<String, ScalarValue> {
+        let mut tree = BTreeMap::new();
+
+        match self.base {
+            JsonBaseValue::Object(ref object) => {
+                for (key, value) in object.values.iter() {
+                    collect_pointers(&mut tree, pointer("", key), value);
+                }
+            }
+            JsonBaseValue::Array(ref values) => {
+                for (index, value) in values.iter().enumerate() {
+                    collect_pointers(&mut tree, format!("/{}", index), value);
+                }
+            }
+        }
+
+        tree
+    }
+}
+
+/// Appends a reference token to a JSON pointer, escaping `~` and `/`
+pub(crate) fn pointer(prefix: &str, token: &str) -> String {
+    format!("{}/{}", prefix, token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn collect_pointers(tree: &mut BTreeMap<String, ScalarValue>, path: String, value: &JsonValue) {
+    let scalar = match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.values.iter() {
+                collect_pointers(tree, pointer(&path, key), value);
+            }
+            return;
+        }
+        JsonValue::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                collect_pointers(tree, format!("{}/{}", path, index), value);
+            }
+            return;
+        }
+        JsonValue::String(s) => ScalarValue::String(s.clone()),
+        JsonValue::Number(n) => ScalarValue::Number(*n),
+        JsonValue::Undefined => ScalarValue::Undefined,
+        JsonValue::Bool(b) => ScalarValue::Bool(*b),
+        JsonValue::Null => ScalarValue::Null,
+    };
+
+    tree.insert(path, scalar);
+}
+
+fn join(prefix: Option<&str>, segment: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{}.{}", prefix, segment),
+        None => String::from(segment),
+    }
+}
+
+fn flatten_object(flat: &mut Vec<(String, JsonValue)>, prefix: Option<&str>, object: Object) {
+    let mut entries: Vec<(String, JsonValue)> = object.values.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (key, value) in entries {
+        flatten_value(flat, join(prefix, &key), value);
+    }
+}
+
+fn flatten_array(
+    flat: &mut Vec<(String, JsonValue)>,
+    prefix: Option<&str>,
+    values: Vec<JsonValue>,
+) {
+    for (index, value) in values.into_iter().enumerate() {
+        flatten_value(flat, join(prefix, &format!("{}", index)), value);
+    }
+}
+
+fn flatten_value(flat: &mut Vec<(String, JsonValue)>, path: String, value: JsonValue) {
+    match value {
+        JsonValue::Object(object) => flatten_object(flat, Some(&path), object),
+        JsonValue::Array(values) => flatten_array(flat, Some(&path), values),
+        scalar => flat.push((path, scalar)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elements::{JsonValue, Map, Object, ScalarValue};
+    use crate::fixtures;
+    use crate::DocumentBuilder;
+
+    #[test]
+    fn flatten_nested_object() {
+        let flat = fixtures::nested_document().into_flat();
+
+        assert_eq!(flat.len(), 1);
+        assert_eq!(flat[0].0, "0.test");
+        assert!(matches!(flat[0].1, JsonValue::String(ref s) if s == "yes"));
+    }
+
+    #[test]
+    fn flatten_sorted_paths() {
+        let flat = DocumentBuilder::object()
+            .insert(
+                "b",
+                JsonValue::Array(vec![JsonValue::Null, JsonValue::Bool(true)]),
+            )
+            .insert("a", JsonValue::Number(1.0))
+            .insert("c", JsonValue::Array(vec![]))
+            .build()
+            .into_flat();
+
+        let paths: Vec<&str> = flat.iter().map(|(path, _)| path.as_str()).collect();
+
+        assert_eq!(paths, vec!["a", "b.0", "b.1"]);
+    }
+
+    #[test]
+    fn btree_nested_object() {
+        let tree = fixtures::nested_document().to_btree();
+
+        assert_eq!(tree.len(), 1);
+        assert_eq!(
+            tree.get("/0/test"),
+            Some(&ScalarValue::String("yes".to_string()))
+        );
+    }
+
+    #[test]
+    fn btree_escaped_pointers() {
+        let tree = DocumentBuilder::object()
+            .insert("b", JsonValue::Array(vec![JsonValue::Null]))
+            .insert("a/b~c", JsonValue::Bool(true))
+            .insert(
+                "c",
+                JsonValue::Object(Object {
+                    size: 0,
+                    values: Map::new(),
+                }),
+            )
+            .build()
+            .to_btree();
+
+        let pointers: Vec<&str> = tree.keys().map(|pointer| pointer.as_str()).collect();
+
+        assert_eq!(pointers, vec!["/a~1b~0c", "/b/0"]);
+        assert_eq!(tree["/b/0"], ScalarValue::Null);
+    }
+}
@@ -0,0 +1,197 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter, Write};
+
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::QJSONDocument;
+
+/// The number of spaces used for every level of the pretty output
+const INDENT: usize = 2;
+
+impl QJSONDocument {
+    /// Converts the base of the document into a compact JSON text.
+    ///
+    /// Values JSON can not represent are converted like `JSON.stringify` does in arrays:
+    /// `undefined`, NaN and infinite numbers become `null`. The keys of objects are sorted.
+    pub fn to_json_string(&self) -> String {
+        let mut out = String::new();
+        // Writing into a String never fails
+        let _ = write_base(&mut out, &self.base, None);
+
+        out
+    }
+
+    /// Converts the base of the document into a JSON text like
+    /// [`to_json_string`](QJSONDocument::to_json_string), but puts every value on its own line
+    /// and indents it by two spaces per level.
+    pub fn to_json_string_pretty(&self) -> String {
+        let mut out = String::new();
+        let _ = write_base(&mut out, &self.base, Some(0));
+
+        out
+    }
+}
+
+/// Writes the base as a compact JSON text, or as a pretty one using the alternate flag (`{:#}`)
+impl Display for QJSONDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let indent = f.alternate().then_some(0);
+        write_base(f, &self.base, indent)
+    }
+}
+
+/// Writes the value as a compact JSON text, or as a pretty one using the alternate flag (`{:#}`)
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let indent = f.alternate().then_some(0);
+        write_value(f, self, indent)
+    }
+}
+
+fn write_base<W: Write>(out: &mut W, base: &JsonBaseValue, indent: Option<usize>) -> fmt::Result {
+    match base {
+        JsonBaseValue::Object(object) => write_object(out, object, indent),
+        JsonBaseValue::Array(values) => write_array(out, values, indent),
+    }
+}
+
+/// Writes a value. The indent is the current level of the pretty output, or `None` for the
+/// compact output.
+fn write_value<W: Write>(out: &mut W, value: &JsonValue, indent: Option<usize>) -> fmt::Result {
+    match value {
+        JsonValue::String(s) => write_string(out, s),
+        JsonValue::Number(n) if n.is_finite() => write!(out, "{}", n),
+        JsonValue::Number(_) | JsonValue::Undefined | JsonValue::Null => out.write_str("null"),
+        JsonValue::Bool(b) => write!(out, "{}", b),
+        JsonValue::Object(object) => write_object(out, object, indent),
+        JsonValue::Array(values) => write_array(out, values, indent),
+    }
+}
+
+fn write_object<W: Write>(out: &mut W, object: &Object, indent: Option<usize>) -> fmt::Result {
+    let mut entries: Vec<(&String, &JsonValue)> = object.values.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    out.write_char('{')?;
+    for (i, (key, value)) in entries.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        write_newline(out, indent.map(|level| level + 1))?;
+
+        write_string(out, key)?;
+        out.write_char(':')?;
+        if indent.is_some() {
+            out.write_char(' ')?;
+        }
+        write_value(out, value, indent.map(|level| level + 1))?;
+    }
+    if !entries.is_empty() {
+        write_newline(out, indent)?;
+    }
+
+    out.write_char('}')
+}
+
+fn write_array<W: Write>(out: &mut W, values: &[JsonValue], indent: Option<usize>) -> fmt::Result {
+    out.write_char('[')?;
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        write_newline(out, indent.map(|level| level + 1))?;
+
+        write_value(out, value, indent.map(|level| level + 1))?;
+    }
+    if !values.is_empty() {
+        write_newline(out, indent)?;
+    }
+
+    out.write_char(']')
+}
+
+/// Starts a new line at the given level of the pretty output. Does nothing for the compact
+/// output.
+fn write_newline<W: Write>(out: &mut W, indent: Option<usize>) -> fmt::Result {
+    if let Some(level) = indent {
+        out.write_char('\n')?;
+        for _ in 0..level * INDENT {
+            out.write_char(' ')?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a quoted string escaped according to RFC 8259. Characters outside of ASCII are
+/// written as they are, since the output is UTF8.
+fn write_string<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{8}' => out.write_str("\\b")?,
+            '\u{C}' => out.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+
+    out.write_char('"')
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elements::JsonValue;
+    use crate::{fixtures, DocumentBuilder};
+
+    #[test]
+    fn escape_strings() {
+        let value = JsonValue::String("a \"quoted\"\nline\\ \u{1} \u{1F600}".to_string());
+
+        assert_eq!(
+            value.to_string(),
+            "\"a \\\"quoted\\\"\\nline\\\\ \\u0001 \u{1F600}\""
+        );
+    }
+
+    #[test]
+    fn compact_json() {
+        let document = fixtures::nested_document();
+
+        assert_eq!(document.to_json_string(), "[{\"test\":\"yes\"}]");
+        assert_eq!(document.to_string(), document.to_json_string());
+    }
+
+    #[test]
+    fn pretty_json() {
+        let document = DocumentBuilder::object()
+            .insert("text", JsonValue::String("say \"hi\"\n".to_string()))
+            .insert(
+                "list",
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.0),
+                    JsonValue::Number(0.5),
+                    JsonValue::Undefined,
+                    JsonValue::Number(f64::NAN),
+                    JsonValue::new_array(),
+                ]),
+            )
+            .insert("empty", JsonValue::new_object())
+            .build();
+
+        let expected = "{\n  \"empty\": {},\n  \"list\": [\n    1,\n    0.5,\n    null,\n    null,\
+                        \n    []\n  ],\n  \"text\": \"say \\\"hi\\\"\\n\"\n}";
+
+        assert_eq!(document.to_json_string_pretty(), expected);
+        assert_eq!(format!("{:#}", document), expected);
+        assert_eq!(
+            document.to_json_string(),
+            "{\"empty\":{},\"list\":[1,0.5,null,null,[]],\"text\":\"say \\\"hi\\\"\\n\"}"
+        );
+    }
+}
@@ -30,28 +30,159 @@
 //! }
 //! ```
 //!
+//! # Features
+//!
+//! - `std` (default): Uses `std`, implements `std::error::Error` for [`QtJsonError`] and
+//!   stores object values in a `HashMap`.
+//! - `alloc`: Allows the usage in `no_std` environments providing `alloc`. Disable the default
+//!   features to use it. Object values are stored in a `BTreeMap` instead.
+//! - `serde`: Adds [`JsonValue::to_serde`](elements::JsonValue::to_serde) to convert a parsed
+//!   value into a `serde_json::Value`.
+//! - `derive`: Adds `#[derive(FromQtJson)]` to convert documents into structs using
+//!   [`QJSONDocument::deserialize`].
+//! - `logging` (default): Logs unusual content like duplicate keys or skipped values using the
+//!   `log` crate. Without it the crate does not depend on `log` and no message is compiled.
+//!   The parsing is not affected. Build using `cargo build --no-default-features --features
+//!   std` to leave it out.
+//! - `verbose-logging`: Logs every element and value using the trace level of the `log`
+//!   crate. Without it the trace messages are not even compiled, which keeps them out of the
+//!   inner loops of the parser. Implies `logging`.
+//! - `wasm`: Adds [`parse_to_jsvalue`] for the usage with `wasm-bindgen`. The parsed document
+//!   is returned as plain JavaScript objects and arrays. Implies `serde`.
+//! - `base64`: Adds [`QJSONDocument::from_base64`](QJSONDocument::from_base64) to parse a
+//!   document stored as base64 text.
+//!
 //! # Disclaimer
 //!
 //! This library has been created by looking at the Qt source code and performing reverse
 //! engineering.
 //! There is a possibility that the code will not work with other Version of Qt JSON documents.
 //! Any help with this library is welcome.
+//!
+//! The binary format has been introduced together with `QJsonDocument` in Qt 5.0, so there is
+//! no older variant written by Qt 4. Every Qt version writing the format uses version 1.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("qt-json requires either the `std` or the `alloc` feature");
+
+extern crate alloc;
+
+/// Discards a log message. The arguments are still type checked, but never evaluated.
+#[allow(unused_macros)]
+macro_rules! discard {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+/// Logs a message of the trace level, if the `verbose-logging` feature is enabled.
+///
+/// The parser traces every single value, so the calls are removed from the build entirely if
+/// the feature is disabled.
+#[cfg(feature = "verbose-logging")]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        log::trace!($($arg)+)
+    };
+}
+
+/// Discards the message, see the definition used with `verbose-logging`
+#[cfg(not(feature = "verbose-logging"))]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        discard!($($arg)+)
+    };
+}
+
+/// Logs a message of the debug level, if the `logging` feature is enabled
+#[cfg(feature = "logging")]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        log::debug!($($arg)+)
+    };
+}
+
+/// Discards the message, see the definition used with `logging`
+#[cfg(not(feature = "logging"))]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        discard!($($arg)+)
+    };
+}
+
+/// Logs a message of the warn level, if the `logging` feature is enabled
+#[cfg(feature = "logging")]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        log::warn!($($arg)+)
+    };
+}
+
+/// Discards the message, see the definition used with `logging`
+#[cfg(not(feature = "logging"))]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        discard!($($arg)+)
+    };
+}
 
-use std::collections::HashMap;
-use std::io::{Cursor, Error, ErrorKind, Read};
+use alloc::format;
+use alloc::vec::Vec;
 
-use byteorder::ReadBytesExt;
-use log::{debug, trace, warn};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 
-use elements::{JsonBaseValue, JsonValue, Object};
+pub use builder::DocumentBuilder;
+pub use deserialize::FromQtJson;
+pub use diff::{diff, Change};
+use elements::{JsonBaseValue, JsonValue, JsonValueRef, Object, Spans, StringEncodings};
+pub use error::QtJsonError;
+pub use options::{
+    NonFinitePolicy, ParseOptions, UndefinedElementPolicy, UnknownTypePolicy, WriteOptions,
+};
+use parser::Parser;
+#[cfg(feature = "derive")]
+pub use qt_json_derive::FromQtJson;
+use reader::Reader;
+pub use schema::{Schema, SchemaError};
+pub use stats::ParseStats;
+pub use visitor::JsonVisitor;
+#[cfg(feature = "wasm")]
+pub use wasm::parse_to_jsvalue;
+
+/// Used by the code generated by the derive macro. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::deserialize::{field, mismatch};
+}
 
+mod builder;
+mod deserialize;
+mod diff;
 pub mod elements;
+mod error;
+#[cfg(test)]
+mod fixtures;
+mod flatten;
+mod json;
+mod options;
+mod parser;
+mod reader;
+mod schema;
+mod stats;
+mod validate;
+mod visitor;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod writer;
 
 /// A QJSONDocument is the root of every parsed JSOn Document.
 /// It consists out of metadata and a base
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct QJSONDocument {
     /// This will be "qbjs" encoded in an u32
     pub tag: u32,
@@ -63,14 +194,15 @@ pub struct QJSONDocument {
 }
 
 /// This is every possible value type in the QBJS format.
-#[derive(Debug, Eq, PartialEq, FromPrimitive)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, FromPrimitive)]
 #[repr(u32)]
-enum QTValueType {
+pub enum QTValueType {
     /// A null value
     Null = 0x0,
     /// A boolean value
     Bool = 0x1,
-    /// A signed integer value represented in the javascript number format (i.e. i64)
+    /// A number. It is either stored as a 64 bit float or, if it is a small integer, inline in
+    /// the value header
     Double = 0x2,
     /// A normal array of character. Can be latin or unicode
     String = 0x3,
@@ -82,299 +214,404 @@ enum QTValueType {
     Undefined = 0x80,
 }
 
-const QT_JSON_TAG: u32 =
-    (('s' as u32) << 24) | (('j' as u32) << 16) | (('b' as u32) << 8) | ('q' as u32);
-
-pub type Endianess = byteorder::LittleEndian;
+impl QTValueType {
+    /// Extracts the type of a value from its 32 bit value header.
+    ///
+    /// Qt only reserves the lowest three bits of the header for the type, so
+    /// [`QTValueType::Undefined`] can not be stored in the type field itself. Writers storing an
+    /// undefined value emit `0x80` in the lowest byte instead, which would otherwise be a `Null`
+    /// carrying a payload. Qt itself always writes `Null` values with an empty payload.
+    /// The latin flags are ignored, so the marker is also recognized for object entries.
+    fn from_header(header: u32) -> Option<Self> {
+        if header & 0xE7 == QTValueType::Undefined as u32 {
+            return Some(QTValueType::Undefined);
+        }
 
-impl QJSONDocument {
-    /// Parses a binary VEC into a QJSONDocument
-    pub fn from_binary(data: Vec<u8>) -> Result<Self, Error> {
-        debug!("[QBJS] Loading data");
+        FromPrimitive::from_u32(header & 0b111)
+    }
+}
 
-        let mut reader = Cursor::new(&data);
+impl TryFrom<Vec<u8>> for QJSONDocument {
+    type Error = QtJsonError;
 
-        let tag = reader.read_u32::<Endianess>()?;
-        let version = reader.read_u32::<Endianess>()?;
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::from_binary(data)
+    }
+}
 
-        assert_eq!(tag, QT_JSON_TAG);
+impl TryFrom<&[u8]> for QJSONDocument {
+    type Error = QtJsonError;
 
-        assert_eq!(version, 1);
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, &ParseOptions::default())
+    }
+}
 
-        debug!("QBJS Version: {}", version);
+const QT_JSON_TAG: u32 =
+    (('s' as u32) << 24) | (('j' as u32) << 16) | (('b' as u32) << 8) | ('q' as u32);
 
-        let elem = Self::load_element(data[8..].to_vec())?;
+/// The size of the header of an element, consisting of the size, the header and the offset of
+/// the table
+pub(crate) const ELEMENT_HEADER_SIZE: usize = 12;
 
-        let base = match elem {
-            JsonValue::Object(o) => JsonBaseValue::Object(o),
-            JsonValue::Array(a) => JsonBaseValue::Array(a),
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "The Base must be either an Array or object",
-                ));
-            }
-        };
+pub type Endianess = byteorder::LittleEndian;
 
-        let doc = QJSONDocument { tag, version, base };
+/// Qt stores every string and value aligned to 4 bytes.
+/// Returns the offset rounded up to the next multiple of 4.
+pub(crate) fn align_to_4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
 
-        debug!("[QBJS] Parsing finished!");
+impl QJSONDocument {
+    /// Parses a binary VEC into a QJSONDocument.
+    ///
+    /// Some writers pad the end of the document to 4 bytes with zeros. Up to 3 such bytes are
+    /// accepted behind the base, any other trailing data is rejected.
+    ///
+    /// Every value is located through the offset stored for it, so documents which have not
+    /// been compacted by Qt and still contain unused data between their values are read as
+    /// well.
+    pub fn from_binary(data: Vec<u8>) -> Result<Self, QtJsonError> {
+        Self::parse(&data, &ParseOptions::default())
+    }
 
-        Ok(doc)
+    /// Parses a whole document from borrowed data, e.g. a memory mapped file.
+    ///
+    /// The data is never copied. Only the decoded values are allocated.
+    pub fn from_slice(data: &[u8]) -> Result<Self, QtJsonError> {
+        Self::parse(data, &ParseOptions::default())
     }
 
-    /// Loads a single element from the binary data.
-    fn load_element(data: Vec<u8>) -> Result<JsonValue, Error> {
-        let mut reader = Cursor::new(&data);
+    /// Parses the binary data of a whole document using the given options
+    pub fn from_binary_with_options(
+        data: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Self, QtJsonError> {
+        Self::parse(data, options)
+    }
 
-        let size = reader.read_u32::<Endianess>()?;
-        let header = reader.read_u32::<Endianess>()?;
-        let offset = reader.read_u32::<Endianess>()?;
+    /// Parses a whole document and records whether each string has been stored as latin or as
+    /// UTF16.
+    ///
+    /// Passing the encodings to [`to_binary_with_options`](QJSONDocument::to_binary_with_options)
+    /// using [`WriteOptions::string_encodings`] reproduces the storage of the strings, even if
+    /// the document has been written by a writer choosing differently than this crate.
+    pub fn from_binary_with_encodings(data: &[u8]) -> Result<(Self, StringEncodings), QtJsonError> {
+        let options = ParseOptions::default();
+        let mut parser = Parser::new(&options);
+        parser.record_encodings();
+
+        let document = Self::load(data, &options, &mut parser)?;
+        Self::check_trailing_data(data)?;
+
+        Ok((document, parser.take_encodings()))
+    }
 
-        let is_object = (header & 0x1) == 1;
-        let len = header >> 1;
+    /// Parses a whole document and records the byte range every value occupies in the data,
+    /// e.g. to point at the origin of a value in a hex editor.
+    ///
+    /// The ranges are kept apart from the values, see [`Spans`]. The range of an element
+    /// covers the whole element including its table, the range of a value stored inside of its
+    /// header covers just the header.
+    pub fn from_binary_with_spans(data: &[u8]) -> Result<(Self, Spans), QtJsonError> {
+        let options = ParseOptions::default();
+        let mut parser = Parser::new(&options);
+        parser.record_spans(data);
+
+        let document = Self::load(data, &options, &mut parser)?;
+        Self::check_trailing_data(data)?;
+
+        Ok((document, parser.take_spans()))
+    }
 
-        trace!("Element Size is: {:#0X}", size);
-        trace!("Element Offset is: {:#0X}", offset);
-        trace!("Element is an object: {}", is_object);
-        trace!("Element elements: {}", len);
+    /// Parses a whole document like [`from_binary`](QJSONDocument::from_binary) and reports the
+    /// progress, e.g. to drive a progress bar.
+    ///
+    /// The callback receives the number of bytes parsed so far and the size of the document.
+    /// It is called after every entry of the base and once more with both values being equal
+    /// after the document has been parsed. The parsed bytes never decrease between two calls.
+    pub fn from_binary_with_progress<F: FnMut(usize, usize)>(
+        data: &[u8],
+        mut progress: F,
+    ) -> Result<Self, QtJsonError> {
+        let options = ParseOptions::default();
+        let mut parser = Parser::with_progress(&options, data, &mut progress);
 
-        let table = data.split_at(offset as usize).1;
+        let document = Self::load(data, &options, &mut parser)?;
+        Self::check_trailing_data(data)?;
+        parser.finish_progress();
 
-        // u32 is 4 bytes
-        trace!("Table len is {}", table.len() / 4);
+        Ok(document)
+    }
 
-        let base = match is_object {
-            true => Self::load_object(&data, table, len, size),
-            false => Self::load_array(&data, table, len, size),
-        };
+    /// Parses the document at the start of the data and returns it together with the number of
+    /// bytes it occupies.
+    ///
+    /// Any data behind the document is ignored, so documents written back to back can be
+    /// parsed by continuing at the returned position.
+    pub fn from_binary_prefix(data: &[u8]) -> Result<(Self, usize), QtJsonError> {
+        let options = ParseOptions::default();
+        let document = Self::load(data, &options, &mut Parser::new(&options))?;
 
-        trace!("{:?}", base);
+        // The size of the base covers the whole element, the header comes in front of it
+        let size = Reader::new(&data[8..]).read_u32()? as usize;
 
-        base
+        Ok((document, 8 + size))
     }
 
-    /**
-     * loads an object from the stream
-     */
-    fn load_object(data: &[u8], offsets: &[u8], len: u32, size: u32) -> Result<JsonValue, Error> {
-        debug!("Loading object ..");
-        trace!("Expected len: {}", len);
-        trace!("Actual len: {}", offsets.len() / 4);
-
-        if offsets.len() / 4 < (len as usize) {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!(
-                    "The object is not the expected size, expected: {}, provided: {}",
-                    len,
-                    offsets.len() / 4
-                ),
-            ));
+    /// Parses documents written back to back until the data is exhausted, e.g. a log file of
+    /// appended records.
+    ///
+    /// Fails if the data ends in the middle of a document, or if anything but a document
+    /// follows one.
+    pub fn from_binary_multi(data: &[u8]) -> Result<Vec<Self>, QtJsonError> {
+        let mut documents = Vec::new();
+        let mut position = 0;
+
+        while position < data.len() {
+            let (document, consumed) = Self::from_binary_prefix(&data[position..])?;
+            documents.push(document);
+            position += consumed;
         }
 
-        let mut offsets = Cursor::new(offsets);
-        let mut values = HashMap::new();
+        Ok(documents)
+    }
 
-        for i in 0..len {
-            trace!("Iterating over entry {}", i);
+    /// Parses the binary data of a whole document including the header
+    fn parse(data: &[u8], options: &ParseOptions) -> Result<Self, QtJsonError> {
+        Self::parse_collecting_stats(data, options).map(|(document, _)| document)
+    }
 
-            let offset = offsets.read_u32::<Endianess>()?;
-            trace!("Entry at offset: {:0X?}", offset);
+    fn parse_collecting_stats(
+        data: &[u8],
+        options: &ParseOptions,
+    ) -> Result<(Self, ParseStats), QtJsonError> {
+        let mut parser = Parser::new(options);
+        let doc = Self::load(data, options, &mut parser)?;
 
-            let element = data.split_at(offset as usize).1;
-            let mut reader = Cursor::new(element);
+        let mut stats = parser.stats();
+        stats.bytes = Self::check_trailing_data(data)?;
 
-            let value_header = reader.read_u32::<Endianess>()?;
-            trace!(" > Value header {:032b}", value_header);
+        Ok((doc, stats))
+    }
 
-            let value_type_number: u32 = value_header & 0b111;
-            let latin_or_int = ((value_header & 0b1000) >> 3) == 1;
-            let latin_key = ((value_header & 0b10000) >> 4) == 1;
-            let orig_value: u32 = (value_header & 0xFFFFFFE0) >> 5;
+    /// Checks that nothing but the zero padding some writers append to align the document to
+    /// 4 bytes follows the base. Returns the size of the document including the header.
+    fn check_trailing_data(data: &[u8]) -> Result<usize, QtJsonError> {
+        // The size of the base covers the whole element, the header comes in front of it
+        let size = 8 + Reader::new(&data[8..]).read_u32()? as usize;
+
+        let trailing = data.get(size..).unwrap_or_default();
+        if trailing.len() > 3 || trailing.iter().any(|&byte| byte != 0) {
+            return Err(QtJsonError::InvalidData(format!(
+                "The document is followed by {} bytes of trailing data",
+                trailing.len()
+            )));
+        }
 
-            let value_type: Option<QTValueType> = FromPrimitive::from_u32(value_type_number);
+        Ok(size)
+    }
 
-            if value_type.is_none() {
-                warn!("Could not parse value at json entry {}\nContinuing. But this might have unacceptable impact", i);
-                debug!("Value type: {:#0X}", value_type_number);
-                debug!("Value value: {:#04X}", orig_value);
-            }
+    /// Parses the header and the base of a document using the given parser
+    fn load(data: &[u8], options: &ParseOptions, parser: &mut Parser) -> Result<Self, QtJsonError> {
+        debug!("[QBJS] Loading data");
 
-            trace!(" > Value of type: {:?}", value_type);
-            trace!(" > Key is latin: {}", latin_key);
-            let key = Self::read_string(&mut reader, latin_key)?;
+        let (tag, version) = Self::read_header(data, options)?;
 
-            trace!(" > Key is: '{}'", key);
-            trace!(" > Reading value of type: {:?}", value_type);
+        let elem = parser.load_element(&data[8..])?;
 
-            let value = Self::decode_value(
-                value_type,
-                orig_value,
-                latin_or_int,
-                latin_key,
-                size as usize,
-                data,
-            )?;
+        let base = match elem {
+            JsonValue::Object(o) => JsonBaseValue::Object(o),
+            JsonValue::Array(a) => JsonBaseValue::Array(a),
+            // An element is always an object or an array
+            _ => return Err(QtJsonError::InvalidBase),
+        };
 
-            trace!(" > Value is: {:?}", value);
+        let doc = QJSONDocument { tag, version, base };
 
-            values.insert(key, value);
-        }
+        debug!("[QBJS] Parsing finished!");
 
-        let object = Object { size: len, values };
+        Ok(doc)
+    }
 
-        trace!("Using object {:?}", object);
+    /// Reads and checks the tag and the version in front of the base element
+    fn read_header(data: &[u8], options: &ParseOptions) -> Result<(u32, u32), QtJsonError> {
+        let mut reader = Reader::new(data);
 
-        Ok(JsonValue::Object(object))
-    }
+        let tag = reader.read_u32()?;
+        let version = reader.read_u32()?;
 
-    fn load_array(data: &[u8], offsets: &[u8], len: u32, size: u32) -> Result<JsonValue, Error> {
-        debug!("Loading array ..");
-        trace!("Expected len: {}", len);
-        trace!("Actual len: {}", offsets.len() / 4);
+        if tag != QT_JSON_TAG {
+            return Err(QtJsonError::InvalidTag(tag));
+        }
 
-        if offsets.len() / 4 < (len as usize) {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!(
-                    "The array is not the expected size, expected: {}, provided: {}",
-                    len,
-                    offsets.len() / 4
-                ),
-            ));
+        if !options.accepted_versions.contains(&version) {
+            return Err(QtJsonError::UnsupportedVersion(version));
         }
 
-        let mut offsets = Cursor::new(offsets);
-        let mut values = Vec::new();
+        if version != 1 {
+            warn!(
+                "QBJS version {} is not known, parsing it like version 1",
+                version
+            );
+        }
 
-        for i in 0..len {
-            trace!("Iterating over entry {}", i);
+        debug!("QBJS Version: {}", version);
 
-            let offset = offsets.read_u32::<Endianess>()?;
-            trace!("Entry at offset: 0x{:0X}", offset);
+        Ok((tag, version))
+    }
 
-            let value_header = offset;
-            trace!(" > Value header {:032b}b", value_header);
+    /// Returns the number of elements in the base of the document
+    pub fn base_len(&self) -> usize {
+        self.base.len()
+    }
 
-            let value_type_number: u16 = (value_header & 0b111) as u16;
-            let latin_or_int = ((value_header & 0b1000) >> 3) == 1;
-            let latin_key = ((value_header & 0b10000) >> 4) == 1;
-            let orig_value: u32 = (value_header & 0xFFFFFFE0) >> 5;
+    /// Returns the base if it is an object
+    pub fn base_as_object(&self) -> Option<&Object> {
+        match self.base {
+            JsonBaseValue::Object(ref object) => Some(object),
+            JsonBaseValue::Array(_) => None,
+        }
+    }
 
-            let value_type: Option<QTValueType> = FromPrimitive::from_u16(value_type_number);
+    /// Returns the values of the base if it is an array
+    pub fn base_as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self.base {
+            JsonBaseValue::Array(ref values) => Some(values),
+            JsonBaseValue::Object(_) => None,
+        }
+    }
 
-            if value_type.is_none() {
-                warn!("Could not parse value at json entry {}\nContinuing. But this might have unacceptable impact", i);
-                debug!("Value type: {:#0X}", value_type_number);
-                debug!("Value value: {:#04X}", orig_value);
+    /// Shrinks the memory of every string, array and object of the document to fit its
+    /// content. Useful for large documents which are kept around after parsing.
+    pub fn compact(&mut self) {
+        match self.base {
+            JsonBaseValue::Object(ref mut object) => object.shrink_to_fit(),
+            JsonBaseValue::Array(ref mut values) => {
+                values.iter_mut().for_each(JsonValue::shrink_to_fit);
+                values.shrink_to_fit();
             }
+        }
+    }
 
-            trace!(" > Reading value of type: {:?}", value_type);
+    /// Reads a file and parses its content into a QJSONDocument
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, QtJsonError> {
+        debug!("[QBJS] Reading {}", path.as_ref().display());
 
-            let value = Self::decode_value(
-                value_type,
-                orig_value,
-                latin_or_int,
-                latin_key,
-                size as usize,
-                data,
-            )?;
+        Self::from_binary(std::fs::read(path)?)
+    }
 
-            trace!(" > Value is: {:?}", value);
+    /// Decodes a document stored as standard base64 with padding and parses it like
+    /// [`from_binary`](QJSONDocument::from_binary)
+    #[cfg(feature = "base64")]
+    pub fn from_base64(s: &str) -> Result<Self, QtJsonError> {
+        use base64::Engine;
 
-            values.push(value);
-        }
+        let data = base64::engine::general_purpose::STANDARD.decode(s)?;
 
-        Ok(JsonValue::Array(values))
+        Self::from_binary(data)
     }
+}
 
-    /// This function is responsible from decoding a value from the given data.
-    /// The value will be passed from the upper declaration function and will
-    /// then be extracted here.
-    ///
-    /// This code has been created using reverse engineering. But it should work for QTJSONv1
-    fn decode_value(
-        value_type: Option<QTValueType>,
-        orig_value: u32,
-        latin_or_int: bool,
-        latin_key: bool,
-        size: usize,
-        data: &[u8],
-    ) -> Result<JsonValue, std::io::Error> {
-        let value = match value_type {
-            Some(QTValueType::Double) => {
-                if latin_or_int {
-                    JsonValue::Number(orig_value.into())
-                } else {
-                    trace!(" > > Value is of type f64");
-                    trace!(" > > Value located at offset: {:0X?}", orig_value);
-
-                    let value_data = data.split_at(orig_value as usize).1;
-                    let mut reader = Cursor::new(value_data);
-                    JsonValue::Number(reader.read_f64::<Endianess>()?)
-                }
-            }
-            Some(QTValueType::String) => {
-                trace!(" > > Value located at offset: {:0X?}", orig_value);
+/// Parses a single element which is not wrapped into a document.
+///
+/// The data must start directly with the element, i.e. the `qbjs` tag and the version which
+/// precede the base of a document must not be part of it. The element begins with its size,
+/// followed by the header and the offset of the table. All offsets are relative to the start
+/// of the element, so the data may not contain anything in front of it. Trailing data behind
+/// the size of the element is ignored.
+///
+/// Unlike a document, the element may be either an object or an array.
+pub fn parse_element(data: &[u8]) -> Result<JsonValue, QtJsonError> {
+    Parser::new(&ParseOptions::default()).load_element(data)
+}
 
-                let value_data = data.split_at(orig_value as usize).1;
-                let mut reader = Cursor::new(value_data);
-                JsonValue::String(Self::read_string(&mut reader, latin_key)?)
-            }
-            Some(QTValueType::Object) | Some(QTValueType::Array) => {
-                trace!(" > > Value located at offset: {:0X?}", orig_value);
+/// Reads the table of a single element without decoding any of its values. This is meant for
+/// tools inspecting the layout of a document.
+///
+/// The data must start directly with the element like for [`parse_element`]. The table of an
+/// object contains the offsets of its entries relative to the start of the element. The table
+/// of an array contains the value headers of its values instead.
+pub fn read_offset_table(data: &[u8]) -> Result<Vec<u32>, QtJsonError> {
+    let mut reader = Reader::new(data);
+
+    let size = reader.read_u32()?;
+    let header = reader.read_u32()?;
+    let offset = reader.read_u32()?;
+
+    let element = data.get(..size as usize).ok_or(QtJsonError::OutOfBounds)?;
+    let mut table = Reader::new(
+        element
+            .get(offset as usize..)
+            .ok_or(QtJsonError::OutOfBounds)?,
+    );
+
+    // Every entry occupies 4 bytes, so the length is checked before anything is allocated
+    let len = (header >> 1) as usize;
+    if len > table.remaining() / 4 {
+        return Err(QtJsonError::InvalidData(format!(
+            "The table claims {} entries, but only {} bytes are available",
+            len,
+            table.remaining()
+        )));
+    }
 
-                trace!(
-                    " > > Trimming {} bytes from object",
-                    data.len() - size as usize
-                );
-                let value_data = data.split_at(size as usize).0;
+    (0..len).map(|_| table.read_u32()).collect()
+}
 
-                trace!(" > > Trimming {} bytes from object top", orig_value);
-                let encapsulated = value_data.split_at(orig_value as usize).1;
-                Self::load_element(Vec::from(encapsulated))?
-            }
-            Some(QTValueType::Bool) => JsonValue::Bool(orig_value != 0),
-            Some(QTValueType::Null) => JsonValue::Null,
-            _ => JsonValue::Undefined,
-        };
+/// Parses a whole document and returns its base, borrowing the strings from the data.
+///
+/// This avoids an allocation for every latin string consisting of ASCII characters only, which
+/// usually are most of the keys. See [`JsonValueRef`](elements::JsonValueRef).
+pub fn parse_borrowed(data: &[u8]) -> Result<JsonValueRef<'_>, QtJsonError> {
+    let options = ParseOptions::default();
+    QJSONDocument::read_header(data, &options)?;
 
-        Ok(value)
-    }
+    let base = Parser::new(&options).load_borrowed(&data[8..])?;
+    QJSONDocument::check_trailing_data(data)?;
 
-    /**
-     * reads a string.
-     * This class is capable of reading a string in UTF16 and UTF8
-     */
-    fn read_string(reader: &mut dyn Read, latin: bool) -> Result<String, Error> {
-        let key_len = reader.read_u16::<Endianess>()?;
+    Ok(base)
+}
 
-        trace!(" --> Reading string, latin:{}, len:{}", latin, key_len);
-        // A latin string defined an ASCII encoded string array. So every character is 8 bits long.
-        if latin {
-            let mut buffer = Vec::new();
-            for _ in 0..key_len {
-                buffer.push(reader.read_u8()?);
-            }
+/// Returns true if the data starts with the `qbjs` tag of a document.
+///
+/// Only the tag is checked, so the data may still be an invalid document. Together with
+/// [`QJSONDocument::from_binary_prefix`] this allows reading documents written back to back
+/// until no further document follows.
+pub fn peek_is_document(data: &[u8]) -> bool {
+    Reader::new(data).read_u32().ok() == Some(QT_JSON_TAG)
+}
 
-            Ok(String::from_utf8_lossy(buffer.as_slice()).parse().unwrap())
-        } else {
-            // By definition any string in JavaScript is UTF16 encoded else.
-            let mut buffer = Vec::new();
-            for _ in 0..key_len {
-                buffer.push(reader.read_u16::<Endianess>()?);
-            }
-            String::from_utf16(buffer.as_slice())
-                .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid UTF16"))
-        }
-    }
+/// Parses a whole document like [`QJSONDocument::from_binary`] and returns statistics about its
+/// content alongside.
+pub fn parse_with_stats(data: &[u8]) -> Result<(QJSONDocument, ParseStats), QtJsonError> {
+    QJSONDocument::parse_collecting_stats(data, &ParseOptions::default())
+}
+
+/// Walks a whole document and reports every element and value to the visitor.
+///
+/// No tree of [`JsonValue`]s is built, so this is suited for large documents which are only
+/// inspected or converted into other structures. See [`JsonVisitor`] for the order of the
+/// events.
+pub fn parse_with_visitor<V: JsonVisitor>(data: &[u8], visitor: &mut V) -> Result<(), QtJsonError> {
+    let options = ParseOptions::default();
+    QJSONDocument::read_header(data, &options)?;
+
+    Parser::with_visitor(&options, visitor).load_element(&data[8..])?;
+    QJSONDocument::check_trailing_data(data)?;
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
-    use crate::elements::{JsonBaseValue, JsonValue};
-    use crate::QJSONDocument;
+    use crate::elements::{JsonBaseValue, JsonValue, Object, StringEncoding};
+    use crate::{
+        align_to_4, fixtures, parse_element, parse_with_stats, peek_is_document, read_offset_table,
+        DocumentBuilder, NonFinitePolicy, ParseOptions, ParseStats, QJSONDocument, QTValueType,
+        QtJsonError, UnknownTypePolicy, WriteOptions, QT_JSON_TAG,
+    };
 
     #[test]
     fn read_object() {
@@ -398,6 +635,48 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_object_accessors() {
+        let parsed = fixtures::object_document();
+
+        let object = match parsed.base {
+            JsonBaseValue::Object(ref object) => object,
+            _ => panic!("Expected object"),
+        };
+
+        assert!(object.contains_key("test"));
+        assert!(!object.contains_key("yes"));
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["test"]);
+        assert_eq!(
+            object.get("test"),
+            Some(&JsonValue::String("yes".to_string()))
+        );
+        assert_eq!(object.get("missing"), None);
+    }
+
+    #[test]
+    fn test_object_retain() {
+        let parsed = fixtures::object_document();
+
+        let mut object = match parsed.base {
+            JsonBaseValue::Object(object) => object,
+            _ => panic!("Expected object"),
+        };
+
+        object.insert("_private", JsonValue::Bool(true));
+        object.insert("other", JsonValue::Null);
+        assert_eq!(object.size, 3);
+
+        object.retain(|key, _| key == "test");
+
+        assert_eq!(object.size, 1);
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["test"]);
+        assert_eq!(
+            object.get("test"),
+            Some(&JsonValue::String("yes".to_string()))
+        );
+    }
+
     #[test]
     fn test_non_latin_number() {
         let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
@@ -413,6 +692,7 @@ mod test {
                     JsonValue::Number(n) => assert_eq!(*n, 10.1),
                     _ => panic!("Expected number"),
                 }
+                assert_eq!(num.qt_type(), QTValueType::Double);
             }
             _ => panic!("Expected array"),
         };
@@ -439,7 +719,7 @@ mod test {
 
     #[test]
     fn test_bool() {
-        let data = b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00!\x00\x00
+        let data = b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00!\x00\x00\
         \x00";
 
         let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
@@ -449,7 +729,7 @@ mod test {
                 assert_eq!(vals.len(), 1);
                 let num = &vals[0];
                 match num {
-                    JsonValue::Bool(n) => assert_eq!(*n, true),
+                    JsonValue::Bool(n) => assert!(*n),
                     _ => panic!("Expected string"),
                 }
             }
@@ -485,4 +765,1053 @@ mod test {
             _ => panic!("Expected array"),
         };
     }
+
+    /// Every value header in the table of an array is relative to the array, so the second
+    /// object must not be located relative to the first one.
+    #[test]
+    fn test_objects_in_array() {
+        let data = b"qbjs\x01\x00\x00\x00\x44\x00\x00\x00\x04\x00\x00\x00\x3C\x00\x00\x00\
+        \x18\x00\x00\x00\x03\x00\x00\x00\x14\x00\x00\x00\x3A\x00\x00\x00\x01\x00a\x00\x0C\x00\x00\x00\
+        \x18\x00\x00\x00\x03\x00\x00\x00\x14\x00\x00\x00\x5A\x00\x00\x00\x01\x00b\x00\x0C\x00\x00\x00\
+        \x85\x01\x00\x00\x85\x04\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        let values = match parsed.base {
+            JsonBaseValue::Array(values) => values,
+            _ => panic!("Expected array"),
+        };
+
+        let objects: Vec<_> = values
+            .iter()
+            .map(|value| match value {
+                JsonValue::Object(object) => object,
+                _ => panic!("Expected object"),
+            })
+            .collect();
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].get("a"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(objects[0].get("b"), None);
+        assert_eq!(objects[1].get("b"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(objects[1].get("a"), None);
+    }
+
+    #[test]
+    fn test_parse_with_stats() {
+        let (document, stats) = parse_with_stats(fixtures::NESTED_DOCUMENT).unwrap();
+
+        assert_eq!(document.base_len(), 1);
+        assert_eq!(
+            stats,
+            ParseStats {
+                objects: 1,
+                arrays: 1,
+                strings: 1,
+                nodes: 3,
+                max_depth: 2,
+                bytes: fixtures::NESTED_DOCUMENT.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_deep_document() {
+        // Alternating objects and arrays, each with a sibling behind the nested element, so
+        // every parent has to continue after its child has been loaded
+        let mut value = JsonValue::Null;
+        for level in (0..100).rev() {
+            value = match level % 2 {
+                0 => {
+                    let mut object = Object::default();
+                    object.insert("nested", value);
+                    object.insert("level", JsonValue::Number(level as f64));
+                    JsonValue::Object(object)
+                }
+                _ => JsonValue::Array(vec![
+                    JsonValue::Number(level as f64),
+                    value,
+                    JsonValue::Bool(true),
+                ]),
+            };
+        }
+        let document = value.into_document().unwrap();
+        let data = document.to_binary().unwrap();
+
+        let (parsed, stats) = parse_with_stats(&data).unwrap();
+
+        assert_eq!(parsed.base, document.base);
+        assert_eq!(stats.max_depth, 100);
+        assert_eq!(stats.objects, 50);
+        assert_eq!(stats.arrays, 50);
+    }
+
+    #[test]
+    fn test_base_accessors() {
+        let object = fixtures::object_document();
+
+        assert_eq!(
+            object.base_as_object().unwrap().get("test"),
+            Some(&JsonValue::String("yes".to_string()))
+        );
+        assert!(object.base_as_array().is_none());
+
+        let array = fixtures::nested_document();
+
+        assert_eq!(array.base_as_array().unwrap().len(), 1);
+        assert!(array.base_as_object().is_none());
+    }
+
+    #[test]
+    fn test_trailing_padding() {
+        let expected = fixtures::object_document();
+
+        let mut padded = fixtures::OBJECT_DOCUMENT.to_vec();
+        padded.extend_from_slice(&[0, 0]);
+
+        assert_eq!(
+            QJSONDocument::from_binary(padded).unwrap().base,
+            expected.base
+        );
+
+        for trailing in [&[0, 1][..], &[0, 0, 0, 0], b"junk"] {
+            let mut data = fixtures::OBJECT_DOCUMENT.to_vec();
+            data.extend_from_slice(trailing);
+
+            assert!(matches!(
+                QJSONDocument::from_binary(data),
+                Err(QtJsonError::InvalidData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_offset_table() {
+        // The object stores the offset of its entry
+        assert_eq!(
+            read_offset_table(&fixtures::OBJECT_DOCUMENT[8..]).unwrap(),
+            vec![0x0C]
+        );
+
+        // The array stores the header of the nested object located at offset 12
+        assert_eq!(
+            read_offset_table(&fixtures::NESTED_DOCUMENT[8..]).unwrap(),
+            vec![0x185]
+        );
+        assert_eq!(
+            read_offset_table(&fixtures::NESTED_DOCUMENT[20..]).unwrap(),
+            vec![0x0C]
+        );
+
+        // A header claiming more entries than the table can hold
+        let mut data = fixtures::OBJECT_DOCUMENT[8..].to_vec();
+        data[4] = 0x07;
+        assert!(matches!(
+            read_offset_table(&data),
+            Err(QtJsonError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut builder = DocumentBuilder::array();
+        for i in 0..10 {
+            builder = builder
+                .push(JsonValue::String(format!("entry {}", i)))
+                .push(JsonValue::Number(i as f64 + 0.5))
+                .push(JsonValue::Array(vec![JsonValue::Bool(true)]));
+        }
+        let data = builder.build().to_binary().unwrap();
+
+        let mut reports = Vec::new();
+        let document = QJSONDocument::from_binary_with_progress(&data, |parsed, total| {
+            reports.push((parsed, total))
+        })
+        .unwrap();
+
+        assert_eq!(document.base_len(), 30);
+        // One report per entry of the base and a final one
+        assert_eq!(reports.len(), 31);
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(reports.iter().all(|&(_, total)| total == data.len()));
+        assert_eq!(reports.last(), Some(&(data.len(), data.len())));
+    }
+
+    #[test]
+    fn test_into_document() {
+        let nested = match fixtures::nested_document().base {
+            JsonBaseValue::Array(mut values) => values.remove(0),
+            _ => panic!("Expected array"),
+        };
+
+        let document = nested.into_document().unwrap();
+        let binary = document.to_binary().unwrap();
+
+        assert_eq!(binary, fixtures::OBJECT_DOCUMENT);
+        assert_eq!(
+            QJSONDocument::from_binary(binary).unwrap().base,
+            document.base
+        );
+        assert!(matches!(
+            JsonValue::Null.into_document(),
+            Err(QtJsonError::InvalidBase)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_tag() {
+        let data =
+            b"qbjx\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::InvalidTag(tag)) => assert_eq!(tag, 0x786A6271),
+            other => panic!("Expected InvalidTag, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let data =
+            b"qbjs\x02\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::UnsupportedVersion(version)) => assert_eq!(version, 2),
+            other => panic!("Expected UnsupportedVersion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_document() {
+        let data = b"qbjs\x01\x00\x00\x00\x10\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::OutOfBounds) => {}
+            other => panic!("Expected OutOfBounds, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf16() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
+        \x01\x00\x00\x00\x00\xD8\x00\x00\x83\x01\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::InvalidUtf16 { path, position }) => {
+                assert_eq!(path, "/0");
+                assert_eq!(position, 0);
+            }
+            other => panic!("Expected InvalidUtf16, got {:?}", other),
+        }
+    }
+
+    /// Replaces the code unit of the only `€` in the data by a lone low surrogate
+    fn corrupt_euro_sign(data: &mut [u8]) {
+        let index = data
+            .windows(2)
+            .position(|unit| unit == [0xAC, 0x20])
+            .unwrap();
+        data[index..index + 2].copy_from_slice(&[0x00, 0xDC]);
+    }
+
+    #[test]
+    fn test_invalid_utf16_position() {
+        let document = DocumentBuilder::object()
+            .insert(
+                "list",
+                JsonValue::Array(vec![JsonValue::from("x"), JsonValue::from("ab€c")]),
+            )
+            .build();
+        let mut data = document.to_binary().unwrap();
+        corrupt_euro_sign(&mut data);
+
+        let error = QJSONDocument::from_binary(data).unwrap_err();
+        match &error {
+            QtJsonError::InvalidUtf16 { path, position } => {
+                assert_eq!(path, "/list/1");
+                assert_eq!(*position, 2);
+            }
+            other => panic!("Expected InvalidUtf16, got {:?}", other),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Invalid UTF16 at code unit 2 of the string at '/list/1'"
+        );
+
+        // The key can not be decoded, so the path ends at its object
+        let document = DocumentBuilder::array()
+            .push(JsonValue::Object(
+                [("€".to_string(), JsonValue::Null)].into_iter().collect(),
+            ))
+            .build();
+        let mut data = document.to_binary().unwrap();
+        corrupt_euro_sign(&mut data);
+
+        match QJSONDocument::from_binary(data) {
+            Err(QtJsonError::InvalidUtf16 { path, position }) => {
+                assert_eq!(path, "/0");
+                assert_eq!(position, 0);
+            }
+            other => panic!("Expected InvalidUtf16, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_utf16_string_round_trip() {
+        let document = crate::DocumentBuilder::array()
+            .push(JsonValue::String("\u{1F600}".to_string()))
+            .build();
+
+        let parsed = QJSONDocument::from_binary(document.to_binary().unwrap()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Array(ref values) => match values.as_slice() {
+                [JsonValue::String(s)] => assert_eq!(s, "\u{1F600}"),
+                other => panic!("Expected a single string, got {:?}", other),
+            },
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    fn test_utf16_string_with_bom_round_trip() {
+        let value = JsonValue::String("\u{FEFF}yes".to_string());
+        let document = crate::DocumentBuilder::array().push(value.clone()).build();
+
+        let parsed = QJSONDocument::from_binary(document.to_binary().unwrap()).unwrap();
+
+        assert_eq!(parsed.base, JsonBaseValue::Array(vec![value]));
+    }
+
+    #[test]
+    fn test_mixed_string_encodings() {
+        // {"a": "x", "b": "y"} where the key "a" and the value "y" are stored as UTF16
+        let data = b"qbjs\x01\x00\x00\x00\x34\x00\x00\x00\x05\x00\x00\x00\x2C\x00\x00\x00\
+        \x0B\x03\x00\x00\x01\x00\x00\x00a\x00\x00\x00\x01\x00x\x00\
+        \x93\x04\x00\x00\x01\x00b\x00\x01\x00\x00\x00y\x00\x00\x00\
+        \x0C\x00\x00\x00\x1C\x00\x00\x00";
+
+        let (document, encodings) = QJSONDocument::from_binary_with_encodings(data).unwrap();
+
+        let object = document.base_as_object().unwrap();
+        assert_eq!(object.get("a"), Some(&JsonValue::String("x".to_string())));
+        assert_eq!(object.get("b"), Some(&JsonValue::String("y".to_string())));
+
+        assert_eq!(encodings.keys.get("/a"), Some(&StringEncoding::Utf16));
+        assert_eq!(encodings.values.get("/a"), Some(&StringEncoding::Latin));
+        assert_eq!(encodings.keys.get("/b"), Some(&StringEncoding::Latin));
+        assert_eq!(encodings.values.get("/b"), Some(&StringEncoding::Utf16));
+
+        let options = WriteOptions {
+            string_encodings: Some(encodings),
+            ..Default::default()
+        };
+        assert_eq!(
+            document.to_binary_with_options(&options).unwrap(),
+            data.to_vec()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "base64")]
+    fn test_from_base64() {
+        use base64::Engine;
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(fixtures::OBJECT_DOCUMENT);
+
+        assert_eq!(
+            QJSONDocument::from_base64(&encoded).unwrap().base,
+            fixtures::object_document().base
+        );
+        assert!(matches!(
+            QJSONDocument::from_base64("not base64!"),
+            Err(QtJsonError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_error_into_io_error() {
+        let error: std::io::Error = QtJsonError::OutOfBounds.into();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        let error: std::io::Error = QtJsonError::InvalidTag(0).into();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_undefined_array_entry() {
+        let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x04\x00\x00\x00\x0C\x00\x00\x00\x80\x00\x00\x00\x00\x00\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Array(ref vals) => {
+                assert_eq!(vals.len(), 2);
+                assert!(matches!(vals[0], JsonValue::Undefined));
+                assert!(matches!(vals[1], JsonValue::Null));
+            }
+            _ => panic!("Expected array"),
+        };
+    }
+
+    #[test]
+    fn test_collapse_undefined_to_null() {
+        let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x04\x00\x00\x00\x0C\x00\x00\x00\x80\x00\x00\x00\x00\x00\x00\x00";
+
+        let options = ParseOptions {
+            collapse_undefined_to_null: false,
+            ..Default::default()
+        };
+        let parsed = QJSONDocument::from_binary_with_options(data, &options).unwrap();
+        assert_eq!(
+            parsed.base,
+            JsonBaseValue::Array(vec![JsonValue::Undefined, JsonValue::Null])
+        );
+
+        let options = ParseOptions {
+            collapse_undefined_to_null: true,
+            ..Default::default()
+        };
+        let parsed = QJSONDocument::from_binary_with_options(data, &options).unwrap();
+        assert_eq!(
+            parsed.base,
+            JsonBaseValue::Array(vec![JsonValue::Null, JsonValue::Null])
+        );
+    }
+
+    #[test]
+    fn test_inline_integers_round_trip() {
+        let data = b"qbjs\x01\x00\x00\x00\x1C\x00\x00\x00\x08\x00\x00\x00\x0C\x00\x00\x00\
+        \xEA\xFF\xFF\xFF\x0A\x48\xE8\x01\xEA\xFF\xFF\x7F\x2A\x00\x00\x80";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        assert_eq!(
+            parsed.base,
+            JsonBaseValue::Array(vec![
+                JsonValue::Number(-1.0),
+                JsonValue::Number(1_000_000.0),
+                JsonValue::Number(67_108_863.0),
+                JsonValue::Number(-67_108_863.0),
+            ])
+        );
+        assert_eq!(parsed.to_binary().unwrap(), data);
+    }
+
+    #[test]
+    fn test_non_finite_number() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\xF8\x7F\x82\x01\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        match parsed.base {
+            JsonBaseValue::Array(ref values) => {
+                assert!(matches!(values[0], JsonValue::Number(n) if n.is_nan()))
+            }
+            _ => panic!("Expected array"),
+        }
+
+        let options = ParseOptions {
+            on_non_finite: NonFinitePolicy::Null,
+            ..Default::default()
+        };
+        let parsed = QJSONDocument::from_binary_with_options(data, &options).unwrap();
+        assert_eq!(parsed.base, JsonBaseValue::Array(vec![JsonValue::Null]));
+
+        let options = ParseOptions {
+            on_non_finite: NonFinitePolicy::Error,
+            ..Default::default()
+        };
+        assert!(matches!(
+            QJSONDocument::from_binary_with_options(data, &options),
+            Err(QtJsonError::NonFiniteNumber(n)) if n.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_max_string_len() {
+        let options = ParseOptions {
+            max_string_len: Some(4),
+            ..Default::default()
+        };
+        assert!(
+            QJSONDocument::from_binary_with_options(fixtures::OBJECT_DOCUMENT, &options).is_ok()
+        );
+
+        // The key "test" is too long
+        let options = ParseOptions {
+            max_string_len: Some(3),
+            ..Default::default()
+        };
+        assert!(matches!(
+            QJSONDocument::from_binary_with_options(fixtures::OBJECT_DOCUMENT, &options),
+            Err(QtJsonError::StringLimitExceeded(3))
+        ));
+    }
+
+    #[test]
+    fn test_table_before_values() {
+        let data =
+            b"qbjs\x01\x00\x00\x00$\x00\x00\x00\x03\x00\x00\x00\x0C\x00\x00\x00\x10\x00\x00\x00\
+        \x9B\x03\x00\x00\x04\x00test\x00\x00\x03\x00yes\x00\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Object(ref object) => {
+                assert_eq!(object.size, 1);
+
+                match object.values.get("test").unwrap() {
+                    JsonValue::String(ref s) => assert_eq!(s, "yes"),
+                    _ => panic!("Expected string"),
+                }
+            }
+            _ => panic!("Expected object"),
+        }
+    }
+
+    #[test]
+    fn test_table_outside_of_element() {
+        // The nested object claims to be 32 bytes long, which places its table behind its end
+        let data = b"qbjs\x01\x00\x00\x00\x34\x00\x00\x00\x02\x00\x00\x00\x30\x00\x00\x00\x20\x00\
+                \x00\x00\x03\x00\x00\x00\x20\x00\x00\x00\x1B\x03\x00\x00\x04\x00test\x00\x00\x03\
+                \x00yes\x00\x00\x00\x0C\x00\x00\x00\x85\x01\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::InvalidData(_)) => {}
+            other => panic!("Expected InvalidData, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_minimal_document() {
+        // The document of `examples/minimal.rs`. The table directly follows the header of the
+        // array, since its only value is stored inside of the value header.
+        let data = b"qbjs\
+        \x01\x00\x00\x00\
+        \x10\x00\x00\x00\
+        \x02\x00\x00\x00\
+        \x0C\x00\x00\x00\
+        \x4A\x01\x00\x00";
+
+        let document = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        assert_eq!(
+            document.base,
+            JsonBaseValue::Array(vec![JsonValue::Number(10.0)])
+        );
+
+        // {"a": 1} with the table in front of the entry it points at
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x03\x00\x00\x00\x0C\x00\x00\x00\
+        \x10\x00\x00\x00\x3A\x00\x00\x00\x01\x00a\x00";
+
+        let document = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        let object = document.base_as_object().unwrap();
+        assert_eq!(object.get("a"), Some(&JsonValue::Number(1.0)));
+    }
+
+    #[test]
+    fn test_non_compacted_document() {
+        // {"a": "x", "b": 2.5} as left behind by modifications without compacting. Stale data
+        // remains in front of and between the entries and values.
+        let data = b"qbjs\x01\x00\x00\x00\x40\x00\x00\x00\x05\x00\x00\x00\x38\x00\x00\x00\
+        \xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x1B\x04\x00\x00\x01\x00a\x00\xEE\xEE\xEE\xEE\
+        \x01\x00x\x00\x12\x06\x00\x00\x01\x00b\x00\xEE\xEE\xEE\xEE\
+        \x00\x00\x00\x00\x00\x00\x04\x40\x14\x00\x00\x00\x24\x00\x00\x00";
+
+        let document = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        let object = document.base_as_object().unwrap();
+        assert_eq!(object.size, 2);
+        assert_eq!(object.get("a"), Some(&JsonValue::String("x".to_string())));
+        assert_eq!(object.get("b"), Some(&JsonValue::Number(2.5)));
+
+        // Writing the document drops the stale data
+        let compacted = document.to_binary().unwrap();
+        assert!(compacted.len() < data.len());
+        assert_eq!(QJSONDocument::from_binary(compacted).unwrap(), document);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_from_file() {
+        let data =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        let path = std::env::temp_dir().join("qt-json-test-from-file.dat");
+        std::fs::write(&path, data).unwrap();
+
+        let parsed = QJSONDocument::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        match parsed.unwrap().base {
+            JsonBaseValue::Array(ref vals) => assert_eq!(vals.len(), 1),
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_from_missing_file() {
+        let path = std::env::temp_dir().join("qt-json-test-does-not-exist.dat");
+
+        match QJSONDocument::from_file(path) {
+            Err(QtJsonError::Io(error)) => assert_eq!(error.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("Expected Io, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_try_from_slice() {
+        let data =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        let parsed = QJSONDocument::try_from(&data[..]).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Array(ref vals) => assert_eq!(vals.len(), 1),
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    fn test_encodings_round_trip() {
+        let (document, encodings) =
+            QJSONDocument::from_binary_with_encodings(fixtures::NESTED_DOCUMENT).unwrap();
+
+        assert_eq!(encodings.keys.get("/0/test"), Some(&StringEncoding::Latin));
+        assert_eq!(
+            encodings.values.get("/0/test"),
+            Some(&StringEncoding::Latin)
+        );
+
+        let options = WriteOptions {
+            string_encodings: Some(encodings),
+            ..Default::default()
+        };
+        assert_eq!(
+            document.to_binary_with_options(&options).unwrap(),
+            fixtures::NESTED_DOCUMENT.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let (document, spans) =
+            QJSONDocument::from_binary_with_spans(fixtures::OBJECT_DOCUMENT).unwrap();
+
+        assert_eq!(
+            document,
+            QJSONDocument::from_slice(fixtures::OBJECT_DOCUMENT).unwrap()
+        );
+        assert_eq!(spans.len(), 2);
+        // The base starts behind the header of the document and covers the rest of it
+        assert_eq!(spans.get(""), Some(&(8..44)));
+        // The length of the string, its characters and the padding
+        assert_eq!(spans.get("/test"), Some(&(32..40)));
+        assert_eq!(&fixtures::OBJECT_DOCUMENT[34..37], b"yes");
+    }
+
+    #[test]
+    fn test_from_slice() {
+        // The document does not have to start at the beginning of the buffer
+        let mut buffer = vec![0xFF; 4];
+        buffer.extend_from_slice(fixtures::OBJECT_DOCUMENT);
+
+        let parsed = QJSONDocument::from_slice(&buffer[4..]).unwrap();
+
+        assert_eq!(parsed.base, fixtures::object_document().base);
+        assert!(matches!(
+            QJSONDocument::from_slice(&buffer),
+            Err(QtJsonError::InvalidTag(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_into_from_vec() {
+        let data = b"qbjx\x01\x00\x00\x00".to_vec();
+
+        let parsed: Result<QJSONDocument, QtJsonError> = data.try_into();
+
+        assert!(matches!(parsed, Err(QtJsonError::InvalidTag(_))));
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let data = b"qbjs\x01\x00\x00\x00\x24\x00\x00\x00\x05\x00\x00\x00\x1C\x00\x00\x00\
+        \x10\x00\x00\x00\x01\x00a\x00\x31\x00\x00\x00\x01\x00a\x00\x0C\x00\x00\x00\x14\x00\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Object(ref object) => {
+                assert_eq!(object.size, 1);
+                assert_eq!(object.values.len(), 1);
+                assert!(matches!(
+                    object.values.get("a"),
+                    Some(JsonValue::Bool(true))
+                ));
+            }
+            _ => panic!("Expected object"),
+        }
+    }
+
+    #[test]
+    fn test_maximum_size() {
+        let data =
+            b"qbjs\x01\x00\x00\x00\xFF\xFF\xFF\xFF\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::OutOfBounds) => {}
+            other => panic!("Expected OutOfBounds, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_maximum_value_offset() {
+        // A double stored at the largest offset a value header can hold
+        let data =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\xE2\xFF\xFF\xFF";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::OutOfBounds) => {}
+            other => panic!("Expected OutOfBounds, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_offset_out_of_bounds() {
+        // The nested object points exactly at the end of the outer array
+        let data =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x05\x02\x00\x00";
+
+        match QJSONDocument::from_binary(data.to_vec()) {
+            Err(QtJsonError::OutOfBounds) => {}
+            other => panic!("Expected OutOfBounds, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_latin1_key() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x03\x00\x00\x00\x14\x00\x00\x00\
+        \x10\x00\x00\x00\x01\x00\xE9\x00\x0C\x00\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Object(ref object) => {
+                assert!(matches!(object.values.get("é"), Some(JsonValue::Null)));
+            }
+            _ => panic!("Expected object"),
+        }
+    }
+
+    /// An array containing `true`, a value of the unknown type 6 and `false`
+    const UNKNOWN_TYPE: &[u8] =
+        b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x06\x00\x00\x00\x0C\x00\x00\x00\
+        \x21\x00\x00\x00\x06\x00\x00\x00\x01\x00\x00\x00";
+
+    fn parse_unknown_type(policy: UnknownTypePolicy) -> Result<QJSONDocument, QtJsonError> {
+        let options = ParseOptions {
+            on_unknown_type: policy,
+            ..Default::default()
+        };
+
+        QJSONDocument::from_binary_with_options(UNKNOWN_TYPE, &options)
+    }
+
+    #[test]
+    fn test_unknown_type_warn() {
+        match parse_unknown_type(UnknownTypePolicy::Warn).unwrap().base {
+            JsonBaseValue::Array(ref vals) => {
+                assert_eq!(vals.len(), 3);
+                assert!(matches!(vals[1], JsonValue::Undefined));
+            }
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_error() {
+        match parse_unknown_type(UnknownTypePolicy::Error) {
+            Err(QtJsonError::UnknownValueType(6)) => {}
+            other => panic!("Expected UnknownValueType, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_skip() {
+        match parse_unknown_type(UnknownTypePolicy::Skip).unwrap().base {
+            JsonBaseValue::Array(ref vals) => {
+                assert_eq!(vals.len(), 2);
+                assert!(matches!(vals[0], JsonValue::Bool(true)));
+                assert!(matches!(vals[1], JsonValue::Bool(false)));
+            }
+            _ => panic!("Expected array"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_skip_in_object() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x03\x00\x00\x00\x14\x00\x00\x00\
+        \x17\x00\x00\x00\x01\x00a\x00\x0C\x00\x00\x00";
+
+        let options = ParseOptions {
+            on_unknown_type: UnknownTypePolicy::Skip,
+            ..Default::default()
+        };
+
+        match QJSONDocument::from_binary_with_options(data, &options)
+            .unwrap()
+            .base
+        {
+            JsonBaseValue::Object(ref object) => {
+                assert_eq!(object.size, 0);
+                assert!(object.values.is_empty());
+            }
+            _ => panic!("Expected object"),
+        }
+    }
+
+    #[test]
+    fn test_inline_integers() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x06\x00\x00\x00\x0C\x00\x00\x00\
+        \xEA\xFF\xFF\xFF\xEA\xFF\xFF\x7F\x0A\x00\x00\x80";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Array(ref vals) => {
+                assert_eq!(
+                    vals,
+                    &vec![
+                        JsonValue::Number(-1.0),
+                        JsonValue::Number(67108863.0),
+                        JsonValue::Number(-67108864.0),
+                    ]
+                );
+            }
+            _ => panic!("Expected array"),
+        };
+    }
+
+    #[test]
+    fn test_base_len() {
+        let parsed = fixtures::object_document();
+        assert_eq!(parsed.base_len(), 1);
+        assert!(!parsed.base.is_empty());
+
+        let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x04\x00\x00\x00\x0C\x00\x00\x00\x80\x00\x00\x00\x00\x00\x00\x00";
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+        assert_eq!(parsed.base_len(), 2);
+    }
+
+    #[test]
+    fn test_parse_element() {
+        let element = parse_element(&fixtures::OBJECT_DOCUMENT[8..]).unwrap();
+        let document = fixtures::object_document();
+
+        match (element, document.base) {
+            (JsonValue::Object(element), JsonBaseValue::Object(base)) => {
+                assert_eq!(element, base);
+                assert_eq!(
+                    element.values.get("test"),
+                    Some(&JsonValue::String("yes".to_string()))
+                );
+            }
+            _ => panic!("Expected object"),
+        }
+
+        assert!(matches!(
+            parse_element(fixtures::OBJECT_DOCUMENT),
+            Err(QtJsonError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_as_array_mut() {
+        let mut element = parse_element(&fixtures::NESTED_DOCUMENT[8..]).unwrap();
+        assert!(element.as_object_mut().is_none());
+
+        element.as_array_mut().unwrap().push(JsonValue::Bool(true));
+
+        let values = element.as_array().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            values[0].as_object().unwrap().get("test"),
+            Some(&JsonValue::String("yes".to_string()))
+        );
+        assert_eq!(values[1], JsonValue::Bool(true));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut document = fixtures::nested_document();
+        let parsed = document.base.clone();
+
+        if let JsonBaseValue::Array(ref mut values) = document.base {
+            values.reserve(16);
+        }
+        document.compact();
+
+        assert_eq!(document.base, parsed);
+        match document.base {
+            JsonBaseValue::Array(ref values) => assert_eq!(values.capacity(), 1),
+            _ => panic!("Expected array"),
+        }
+        assert_eq!(document.to_binary().unwrap(), fixtures::NESTED_DOCUMENT);
+    }
+
+    #[test]
+    fn test_accepted_versions() {
+        let data =
+            b"qbjs\x02\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        assert!(matches!(
+            QJSONDocument::try_from(&data[..]),
+            Err(QtJsonError::UnsupportedVersion(2))
+        ));
+
+        let options = ParseOptions {
+            accepted_versions: vec![1, 2],
+            ..Default::default()
+        };
+        let parsed = QJSONDocument::from_binary_with_options(data, &options).unwrap();
+
+        assert_eq!(parsed.version, 2);
+        assert_eq!(
+            parsed.base,
+            JsonBaseValue::Array(vec![JsonValue::Number(10.0)])
+        );
+    }
+
+    #[test]
+    fn test_from_binary_prefix() {
+        let number =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+        let mut stream = number.to_vec();
+        stream.extend_from_slice(fixtures::OBJECT_DOCUMENT);
+
+        let (first, consumed) = QJSONDocument::from_binary_prefix(&stream).unwrap();
+        assert_eq!(consumed, number.len());
+        assert_eq!(
+            first.base,
+            JsonBaseValue::Array(vec![JsonValue::Number(10.0)])
+        );
+
+        let (second, consumed) = QJSONDocument::from_binary_prefix(&stream[consumed..]).unwrap();
+        assert_eq!(consumed, fixtures::OBJECT_DOCUMENT.len());
+        assert_eq!(second.base_len(), 1);
+    }
+
+    #[test]
+    fn test_from_binary_multi() {
+        let number =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+        let mut stream = number.to_vec();
+        stream.extend_from_slice(fixtures::OBJECT_DOCUMENT);
+        stream.extend_from_slice(number);
+
+        let documents = QJSONDocument::from_binary_multi(&stream).unwrap();
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0], QJSONDocument::from_slice(number).unwrap());
+        assert_eq!(
+            documents[1],
+            QJSONDocument::from_slice(fixtures::OBJECT_DOCUMENT).unwrap()
+        );
+        assert_eq!(documents[2], documents[0]);
+
+        assert_eq!(QJSONDocument::from_binary_multi(&[]).unwrap(), Vec::new());
+
+        // The last document is cut off
+        stream.truncate(stream.len() - 4);
+        assert!(QJSONDocument::from_binary_multi(&stream).is_err());
+    }
+
+    #[test]
+    fn test_peek_is_document() {
+        let number =
+            b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\x4A\x01\x00\x00";
+
+        let mut stream = number.to_vec();
+        stream.extend_from_slice(number);
+        // Trailing data which is not a document
+        stream.extend_from_slice(b"\x00\x00");
+
+        let mut documents = Vec::new();
+        let mut position = 0;
+        while peek_is_document(&stream[position..]) {
+            let (document, consumed) =
+                QJSONDocument::from_binary_prefix(&stream[position..]).unwrap();
+            documents.push(document);
+            position += consumed;
+        }
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(position, 2 * number.len());
+        assert!(!peek_is_document(b"qbj"));
+        assert!(!peek_is_document(b""));
+    }
+
+    #[test]
+    fn test_empty_base() {
+        let object = b"qbjs\x01\x00\x00\x00\x0C\x00\x00\x00\x01\x00\x00\x00\x0C\x00\x00\x00";
+        let parsed = QJSONDocument::from_binary(object.to_vec()).unwrap();
+
+        match parsed.base {
+            JsonBaseValue::Object(ref object) => {
+                assert_eq!(object.size, 0);
+                assert!(object.values.is_empty());
+            }
+            _ => panic!("Expected object"),
+        }
+        assert_eq!(parsed.to_binary().unwrap(), object.to_vec());
+
+        let array = b"qbjs\x01\x00\x00\x00\x0C\x00\x00\x00\x00\x00\x00\x00\x0C\x00\x00\x00";
+        let parsed = QJSONDocument::from_binary(array.to_vec()).unwrap();
+
+        assert_eq!(parsed.base, JsonBaseValue::Array(vec![]));
+        assert_eq!(parsed.to_binary().unwrap(), array.to_vec());
+
+        let nested = QJSONDocument {
+            tag: QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![JsonValue::Array(vec![])]),
+        };
+        let parsed = QJSONDocument::from_binary(nested.to_binary().unwrap()).unwrap();
+
+        assert_eq!(parsed, nested);
+    }
+
+    #[test]
+    fn test_align_to_4() {
+        assert_eq!(align_to_4(0), 0);
+        assert_eq!(align_to_4(1), 4);
+        assert_eq!(align_to_4(4), 4);
+        assert_eq!(align_to_4(5), 8);
+        assert_eq!(align_to_4(7), 8);
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        use crate::elements::JsonValueRef;
+        use alloc::borrow::Cow;
+
+        let borrowed = crate::parse_borrowed(fixtures::OBJECT_DOCUMENT).unwrap();
+
+        match borrowed {
+            JsonValueRef::Object(ref values) => {
+                let (key, value) = values.iter().next().unwrap();
+                assert!(matches!(key, Cow::Borrowed("test")));
+                assert!(matches!(value, JsonValueRef::String(Cow::Borrowed("yes"))));
+            }
+            _ => panic!("Expected object"),
+        }
+
+        let document = fixtures::object_document();
+        match (borrowed.into_owned(), document.base) {
+            (JsonValue::Object(borrowed), JsonBaseValue::Object(owned)) => {
+                assert_eq!(borrowed, owned)
+            }
+            _ => panic!("Expected object"),
+        }
+    }
 }
@@ -0,0 +1,107 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::elements::StringEncodings;
+
+/// Options controlling how a document is parsed.
+///
+/// ```rust
+/// use qt_json::{ParseOptions, UnknownTypePolicy};
+///
+/// let options = ParseOptions {
+///     on_unknown_type: UnknownTypePolicy::Error,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// What to do when a value header contains an unknown type
+    pub on_unknown_type: UnknownTypePolicy,
+    /// The maximum number of nested objects and arrays, including the base. Deeper documents
+    /// are rejected with
+    /// [`QtJsonError::DepthLimitExceeded`](crate::QtJsonError::DepthLimitExceeded).
+    pub max_depth: usize,
+    /// The document versions which are parsed. Only version 1 is known, every other version is
+    /// parsed as if it was version 1 after logging a warning.
+    pub accepted_versions: Vec<u32>,
+    /// Decodes `undefined` values as `null`, including values of an unknown type decoded by
+    /// [`UnknownTypePolicy::Warn`]. Useful if the values are converted into JSON afterwards.
+    pub collapse_undefined_to_null: bool,
+    /// What to do when a stored number is NaN or infinite, which can not be represented in JSON
+    pub on_non_finite: NonFinitePolicy,
+    /// The maximum number of characters of a key or a string value. UTF16 strings count code
+    /// units. Longer strings are rejected with
+    /// [`QtJsonError::StringLimitExceeded`](crate::QtJsonError::StringLimitExceeded) before
+    /// anything is allocated for them.
+    pub max_string_len: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            on_unknown_type: UnknownTypePolicy::default(),
+            max_depth: 128,
+            accepted_versions: vec![1],
+            collapse_undefined_to_null: false,
+            on_non_finite: NonFinitePolicy::default(),
+            max_string_len: None,
+        }
+    }
+}
+
+/// Defines how values with an unknown type are handled
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum UnknownTypePolicy {
+    /// Log a warning and decode the value as `Undefined`
+    #[default]
+    Warn,
+    /// Abort parsing with [`QtJsonError::UnknownValueType`](crate::QtJsonError::UnknownValueType)
+    Error,
+    /// Drop the value. Objects will not contain the key and arrays will be shorter.
+    Skip,
+}
+
+/// Defines what [`JsonValue::prune_undefined_with`](crate::elements::JsonValue::prune_undefined_with)
+/// does with `undefined` elements of arrays
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum UndefinedElementPolicy {
+    /// Drop the element, so the array gets shorter
+    #[default]
+    Remove,
+    /// Replace the element with `null`, so the indices of the other elements are kept
+    Null,
+}
+
+/// Defines how NaN and infinite numbers are handled
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum NonFinitePolicy {
+    /// Decode the number as it is
+    #[default]
+    Keep,
+    /// Decode the number as `null`, like `JSON.stringify` does
+    Null,
+    /// Abort parsing with [`QtJsonError::NonFiniteNumber`](crate::QtJsonError::NonFiniteNumber)
+    Error,
+}
+
+/// Options controlling how a document is serialized.
+///
+/// ```rust
+/// use qt_json::WriteOptions;
+///
+/// let options = WriteOptions {
+///     max_output_size: Some(1024 * 1024),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// The maximum number of bytes the serialized document may occupy, including the header.
+    /// Larger documents are rejected before anything is written.
+    pub max_output_size: Option<usize>,
+    /// The storage of the strings as recorded by
+    /// [`from_binary_with_encodings`](crate::QJSONDocument::from_binary_with_encodings).
+    /// Recorded strings keep their storage, where latin storage is only used if every
+    /// character fits into a single byte. Every other string is stored as latin if possible.
+    pub string_encodings: Option<StringEncodings>,
+}
@@ -0,0 +1,1138 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ops::Range;
+
+use byteorder::ByteOrder;
+
+use crate::elements::{
+    JsonValue, JsonValueRef, Map, Object, Spans, StringEncoding, StringEncodings,
+};
+use crate::flatten::pointer;
+use crate::options::{NonFinitePolicy, ParseOptions, UnknownTypePolicy};
+use crate::reader::Reader;
+use crate::stats::ParseStats;
+use crate::{Endianess, JsonVisitor, QTValueType, QtJsonError, ELEMENT_HEADER_SIZE};
+
+/// A value which can be decoded without loading a nested element
+pub(crate) enum Scalar<'a> {
+    String(Cow<'a, str>),
+    Number(f64),
+    Bool(bool),
+    Null,
+    Undefined,
+}
+
+impl From<Scalar<'_>> for JsonValue {
+    fn from(scalar: Scalar<'_>) -> Self {
+        match scalar {
+            Scalar::String(s) => JsonValue::String(s.into_owned()),
+            Scalar::Number(n) => JsonValue::Number(n),
+            Scalar::Bool(b) => JsonValue::Bool(b),
+            Scalar::Null => JsonValue::Null,
+            Scalar::Undefined => JsonValue::Undefined,
+        }
+    }
+}
+
+/// Builds the values while the parser walks the elements.
+/// This allows the same parser to produce owned and borrowed values.
+pub(crate) trait Tree<'a> {
+    type Value: Debug;
+    type Object: Debug;
+
+    fn scalar(scalar: Scalar<'a>) -> Self::Value;
+    fn new_object() -> Self::Object;
+    /// Inserts an entry and returns true if the key has already been present
+    fn insert(object: &mut Self::Object, key: Cow<'a, str>, value: Self::Value) -> bool;
+    fn object(object: Self::Object) -> Self::Value;
+    fn array(values: Vec<Self::Value>) -> Self::Value;
+}
+
+/// Builds [`JsonValue`]s, every string is copied
+pub(crate) struct Owned;
+
+impl<'a> Tree<'a> for Owned {
+    type Value = JsonValue;
+    type Object = Map<String, JsonValue>;
+
+    fn scalar(scalar: Scalar<'a>) -> JsonValue {
+        scalar.into()
+    }
+
+    fn new_object() -> Self::Object {
+        Map::new()
+    }
+
+    fn insert(object: &mut Self::Object, key: Cow<'a, str>, value: JsonValue) -> bool {
+        object.insert(key.into_owned(), value).is_some()
+    }
+
+    fn object(values: Self::Object) -> JsonValue {
+        // Duplicate keys collapse into a single value, so the header length is not trustworthy
+        JsonValue::Object(Object {
+            size: values.len() as u32,
+            values,
+        })
+    }
+
+    fn array(values: Vec<JsonValue>) -> JsonValue {
+        JsonValue::Array(values)
+    }
+}
+
+/// Builds [`JsonValueRef`]s borrowing the strings from the data wherever possible
+pub(crate) struct Borrowed;
+
+impl<'a> Tree<'a> for Borrowed {
+    type Value = JsonValueRef<'a>;
+    type Object = Map<Cow<'a, str>, JsonValueRef<'a>>;
+
+    fn scalar(scalar: Scalar<'a>) -> JsonValueRef<'a> {
+        match scalar {
+            Scalar::String(s) => JsonValueRef::String(s),
+            Scalar::Number(n) => JsonValueRef::Number(n),
+            Scalar::Bool(b) => JsonValueRef::Bool(b),
+            Scalar::Null => JsonValueRef::Null,
+            Scalar::Undefined => JsonValueRef::Undefined,
+        }
+    }
+
+    fn new_object() -> Self::Object {
+        Map::new()
+    }
+
+    fn insert(object: &mut Self::Object, key: Cow<'a, str>, value: JsonValueRef<'a>) -> bool {
+        object.insert(key, value).is_some()
+    }
+
+    fn object(values: Self::Object) -> JsonValueRef<'a> {
+        JsonValueRef::Object(values)
+    }
+
+    fn array(values: Vec<JsonValueRef<'a>>) -> JsonValueRef<'a> {
+        JsonValueRef::Array(values)
+    }
+}
+
+/// An element whose entries are being loaded by [`Parser::load`]
+struct Frame<'a, T: Tree<'a>> {
+    /// The data of the element, limited to its size
+    data: &'a [u8],
+    /// The part of the table which has not been read yet
+    table: Reader<'a>,
+    len: u32,
+    /// The index of the entry being loaded
+    index: u32,
+    /// The length of [`Parser::path`] before any entry has been loaded
+    path: usize,
+    values: Values<'a, T>,
+}
+
+/// The entries of an element loaded so far
+enum Values<'a, T: Tree<'a>> {
+    /// The entries of an object and the key of the entry being loaded
+    Object(T::Object, Option<Cow<'a, str>>),
+    Array(Vec<T::Value>),
+}
+
+/// The result of [`Parser::decode_value`]
+enum Decoded<'a, V> {
+    /// A decoded scalar, or `None` if the value is skipped
+    Value(Option<V>),
+    /// The data of a nested element, which still needs to be loaded
+    Element(&'a [u8]),
+}
+
+/// Reports how far the parser has advanced, see [`Parser::with_progress`]
+struct Progress<'v> {
+    callback: &'v mut dyn FnMut(usize, usize),
+    /// The memory address of the start of the document
+    start: usize,
+    /// The size of the document
+    total: usize,
+    /// The memory address behind the furthest byte decoded so far
+    furthest: usize,
+}
+
+/// Walks the binary data and decodes every element using the given options.
+pub(crate) struct Parser<'o, 'v> {
+    options: &'o ParseOptions,
+    /// The number of elements currently being loaded
+    depth: usize,
+    /// The start addresses of the elements currently being loaded
+    ancestors: Vec<usize>,
+    /// The memory ranges of the elements loaded completely, as a map from the start to the end
+    /// address. Nested elements lie within their parent, so only the outermost are kept.
+    loaded: BTreeMap<usize, usize>,
+    /// Counts the loaded values, see [`Parser::stats`]
+    stats: ParseStats,
+    /// Collects the encodings of the strings, see [`Parser::record_encodings`]
+    encodings: Option<StringEncodings>,
+    /// Collects the byte ranges of the values together with the address of the document
+    /// they are relative to, see [`Parser::record_spans`]
+    spans: Option<(usize, Spans)>,
+    /// The JSON pointer of the value currently being loaded. Only maintained while the
+    /// encodings or the spans are recorded.
+    path: String,
+    /// Receives the events instead of building the values, see [`Parser::with_visitor`]
+    visitor: Option<&'v mut dyn JsonVisitor>,
+    progress: Option<Progress<'v>>,
+}
+
+impl<'o, 'v> Parser<'o, 'v> {
+    pub(crate) fn new(options: &'o ParseOptions) -> Self {
+        Parser {
+            options,
+            depth: 0,
+            ancestors: Vec::new(),
+            loaded: BTreeMap::new(),
+            stats: ParseStats::default(),
+            encodings: None,
+            spans: None,
+            path: String::new(),
+            visitor: None,
+            progress: None,
+        }
+    }
+
+    /// Creates a parser reporting every element and value to the visitor.
+    /// The loaded elements are always empty, since no values are kept.
+    pub(crate) fn with_visitor(
+        options: &'o ParseOptions,
+        visitor: &'v mut dyn JsonVisitor,
+    ) -> Self {
+        Parser {
+            options,
+            depth: 0,
+            ancestors: Vec::new(),
+            loaded: BTreeMap::new(),
+            stats: ParseStats::default(),
+            encodings: None,
+            spans: None,
+            path: String::new(),
+            visitor: Some(visitor),
+            progress: None,
+        }
+    }
+
+    /// Creates a parser reporting its progress through the document after every entry of the
+    /// base. The callback receives the number of bytes from the start of the document up to
+    /// the furthest byte decoded so far, and the size of the document.
+    pub(crate) fn with_progress(
+        options: &'o ParseOptions,
+        document: &[u8],
+        callback: &'v mut dyn FnMut(usize, usize),
+    ) -> Self {
+        let start = document.as_ptr() as usize;
+
+        Parser {
+            progress: Some(Progress {
+                callback,
+                start,
+                total: document.len(),
+                furthest: start,
+            }),
+            ..Parser::new(options)
+        }
+    }
+
+    /// Notes that the data up to the given memory address has been decoded
+    fn advance(&mut self, address: usize) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.furthest = progress.furthest.max(address);
+        }
+    }
+
+    /// Passes the progress to the callback. Every report is at least as far as the previous
+    /// one, since only the furthest byte is tracked.
+    fn report_progress(&mut self) {
+        if let Some(progress) = self.progress.as_mut() {
+            let parsed = (progress.furthest - progress.start).min(progress.total);
+            (progress.callback)(parsed, progress.total);
+        }
+    }
+
+    /// Reports the whole document as parsed once it has been loaded successfully
+    pub(crate) fn finish_progress(&mut self) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.furthest = progress.start + progress.total;
+        }
+
+        self.report_progress();
+    }
+
+    /// Loads a single element from the binary data.
+    ///
+    /// The data must start at the element. Nested elements are parsed from sub slices of the
+    /// data, so no element is ever copied.
+    pub(crate) fn load_element(&mut self, data: &[u8]) -> Result<JsonValue, QtJsonError> {
+        self.load::<Owned>(data)
+    }
+
+    /// Loads a single element like [`Parser::load_element`], but borrows the strings from the
+    /// data wherever possible.
+    pub(crate) fn load_borrowed<'a>(
+        &mut self,
+        data: &'a [u8],
+    ) -> Result<JsonValueRef<'a>, QtJsonError> {
+        self.load::<Borrowed>(data)
+    }
+
+    /// Returns the statistics of every element loaded so far. The number of bytes is not known
+    /// to the parser and always zero.
+    pub(crate) fn stats(&self) -> ParseStats {
+        self.stats
+    }
+
+    /// Records the encoding of every key and string value loaded from now on
+    pub(crate) fn record_encodings(&mut self) {
+        self.encodings = Some(StringEncodings::default());
+    }
+
+    /// Returns the encodings recorded since [`Parser::record_encodings`] has been called
+    pub(crate) fn take_encodings(&mut self) -> StringEncodings {
+        self.encodings.take().unwrap_or_default()
+    }
+
+    /// Records the byte range of every value loaded from now on. The ranges are relative to
+    /// the start of the given document.
+    pub(crate) fn record_spans(&mut self, document: &[u8]) {
+        self.spans = Some((document.as_ptr() as usize, Spans::new()));
+    }
+
+    /// Returns the spans recorded since [`Parser::record_spans`] has been called
+    pub(crate) fn take_spans(&mut self) -> Spans {
+        self.spans
+            .take()
+            .map(|(_, spans)| spans)
+            .unwrap_or_default()
+    }
+
+    /// Whether the JSON pointer of the current value is needed
+    fn tracks_paths(&self) -> bool {
+        self.encodings.is_some() || self.spans.is_some()
+    }
+
+    /// Records the memory addresses of the current value, if the spans are recorded
+    fn record_span(&mut self, addresses: Range<usize>) {
+        if let Some((start, spans)) = self.spans.as_mut() {
+            let span = addresses.start - *start..addresses.end - *start;
+            spans.insert(self.path.clone(), span);
+        }
+    }
+
+    /// Loads an element and everything nested in it.
+    ///
+    /// Nested elements are not loaded recursively. Every element being loaded is kept as a
+    /// [`Frame`] on an explicit stack instead, so the stack usage of the parser does not grow
+    /// with the depth of the document.
+    fn load<'a, T: Tree<'a>>(&mut self, data: &'a [u8]) -> Result<T::Value, QtJsonError> {
+        let depth = self.depth;
+        let ancestors = self.ancestors.len();
+        let path = self.path.len();
+
+        let value = self.load_iterative::<T>(data);
+
+        // A failed load leaves its elements open, they are closed here
+        if value.is_err() {
+            self.depth = depth;
+            self.ancestors.truncate(ancestors);
+            self.path.truncate(path);
+        }
+
+        value
+    }
+
+    fn load_iterative<'a, T: Tree<'a>>(&mut self, data: &'a [u8]) -> Result<T::Value, QtJsonError> {
+        let mut parents: Vec<Frame<'a, T>> = Vec::new();
+        let mut frame = self.open::<T>(data)?;
+
+        loop {
+            let next = self
+                .next_element(&mut frame)
+                .map_err(|error| Self::locate(error, &parents, &frame))?;
+
+            match next {
+                // The entry is a nested element, its parent continues once it has been loaded
+                Some(data) => {
+                    let nested = self.open::<T>(data)?;
+                    parents.push(core::mem::replace(&mut frame, nested));
+                }
+                None => {
+                    let value = self.close(frame);
+
+                    frame = match parents.pop() {
+                        Some(parent) => parent,
+                        None => return Ok(value),
+                    };
+                    self.finish_entry(&mut frame, Some(value));
+                }
+            }
+        }
+    }
+
+    /// Adds the path of the entry being loaded to an error which does not know it yet
+    fn locate<'a, T: Tree<'a>>(
+        error: QtJsonError,
+        parents: &[Frame<'a, T>],
+        frame: &Frame<'a, T>,
+    ) -> QtJsonError {
+        let position = match error {
+            QtJsonError::InvalidUtf16 { position, .. } => position,
+            error => return error,
+        };
+
+        // Every open element is loading one of its entries. The key of an entry is only known
+        // once it has been decoded, so an invalid key stops at the path of its object.
+        let mut path = String::new();
+        for frame in parents.iter().chain(core::iter::once(frame)) {
+            path = match &frame.values {
+                Values::Object(_, Some(key)) => pointer(&path, key),
+                Values::Object(_, None) => break,
+                Values::Array(_) => format!("{}/{}", path, frame.index),
+            };
+        }
+
+        QtJsonError::InvalidUtf16 { path, position }
+    }
+
+    /// Starts loading an element. The entries are loaded by [`Parser::next_element`].
+    fn open<'a, T: Tree<'a>>(&mut self, data: &'a [u8]) -> Result<Frame<'a, T>, QtJsonError> {
+        if self.depth >= self.options.max_depth {
+            return Err(QtJsonError::DepthLimitExceeded(self.options.max_depth));
+        }
+
+        // An element whose value points back at the element itself, or at one of its parents,
+        // would be loaded over and over again until the depth limit is hit. Every visit could
+        // fan out again, so such a document is rejected as soon as the cycle is found.
+        let start = data.as_ptr() as usize;
+        if self.ancestors.contains(&start) {
+            return Err(QtJsonError::InvalidData(String::from(
+                "An element references itself or one of its parents",
+            )));
+        }
+
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        self.ancestors.push(start);
+
+        self.read_element::<T>(data)
+    }
+
+    fn read_element<'a, T: Tree<'a>>(
+        &mut self,
+        data: &'a [u8],
+    ) -> Result<Frame<'a, T>, QtJsonError> {
+        let mut reader = Reader::new(data);
+
+        let size = reader.read_u32()?;
+        let header = reader.read_u32()?;
+        let offset = reader.read_u32()?;
+
+        let is_object = (header & 0x1) == 1;
+        let len = header >> 1;
+
+        trace!("Element Size is: {:#0X}", size);
+        trace!("Element Offset is: {:#0X}", offset);
+        trace!("Element is an object: {}", is_object);
+        trace!("Element elements: {}", len);
+
+        // Every entry needs at least a 4 byte offset in the table. A length which cannot fit
+        // into the data is rejected before anything is allocated for it.
+        match Self::table_size(len as usize) {
+            Some(table_size) if table_size <= data.len() => {}
+            _ => {
+                return Err(QtJsonError::InvalidData(format!(
+                    "The element claims {} entries, but only {} bytes are available",
+                    len,
+                    data.len()
+                )))
+            }
+        }
+
+        // An element is at least as large as its header, a smaller size cannot be valid
+        if (size as usize) < ELEMENT_HEADER_SIZE {
+            return Err(QtJsonError::InvalidData(format!(
+                "The element claims a size of {} bytes, which is smaller than its header",
+                size
+            )));
+        }
+
+        // The size covers the whole element. Anything behind it belongs to the parent element
+        // and must never be interpreted as a part of this one.
+        let element = data.get(..size as usize).ok_or(QtJsonError::OutOfBounds)?;
+
+        // Qt never stores an element twice. Entries sharing an element, or elements overlapping
+        // each other, would make the parser decode the same data over and over again, which
+        // grows exponentially with the depth of the document.
+        let start = element.as_ptr() as usize;
+        let end = start + element.len();
+        let overlaps = self
+            .loaded
+            .range(..end)
+            .next_back()
+            .is_some_and(|(_, &loaded_end)| loaded_end > start);
+        if overlaps {
+            return Err(QtJsonError::InvalidData(String::from(
+                "An element overlaps an element loaded before",
+            )));
+        }
+
+        // The table offset and all value offsets are relative to the start of the element.
+        // Therefore the table can either be located in front of or behind the values.
+        let table = element
+            .get(offset as usize..)
+            .ok_or(QtJsonError::OutOfBounds)?;
+
+        // u32 is 4 bytes
+        trace!("Table len is {}", table.len() / 4);
+
+        let kind = match is_object {
+            true => "object",
+            false => "array",
+        };
+        debug!("Loading {} ..", kind);
+        trace!("Expected len: {}", len);
+        trace!("Actual len: {}", table.len() / 4);
+
+        if table.len() / 4 < (len as usize) {
+            return Err(QtJsonError::InvalidData(format!(
+                "The {} is not the expected size, expected: {}, provided: {}",
+                kind,
+                len,
+                table.len() / 4
+            )));
+        }
+
+        self.stats.nodes += 1;
+        let values = match is_object {
+            true => {
+                self.stats.objects += 1;
+                if let Some(visitor) = self.visitor.as_mut() {
+                    visitor.begin_object(len as usize);
+                }
+
+                Values::Object(T::new_object(), None)
+            }
+            false => {
+                self.stats.arrays += 1;
+                if let Some(visitor) = self.visitor.as_mut() {
+                    visitor.begin_array(len as usize);
+                }
+
+                Values::Array(Vec::with_capacity(len as usize))
+            }
+        };
+
+        Ok(Frame {
+            data: element,
+            table: Reader::new(table),
+            len,
+            index: 0,
+            path: self.path.len(),
+            values,
+        })
+    }
+
+    /// Loads the entries of an element up to the next nested element and returns its data.
+    /// Returns `None` once every entry has been loaded.
+    fn next_element<'a, T: Tree<'a>>(
+        &mut self,
+        frame: &mut Frame<'a, T>,
+    ) -> Result<Option<&'a [u8]>, QtJsonError> {
+        while frame.index < frame.len {
+            trace!("Iterating over entry {}", frame.index);
+
+            let value = match frame.values {
+                Values::Object(..) => self.decode_entry(frame)?,
+                Values::Array(_) => self.decode_element(frame)?,
+            };
+
+            match value {
+                Decoded::Element(data) => return Ok(Some(data)),
+                Decoded::Value(value) => self.finish_entry(frame, value),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decodes the next entry of an object
+    fn decode_entry<'a, T: Tree<'a>>(
+        &mut self,
+        frame: &mut Frame<'a, T>,
+    ) -> Result<Decoded<'a, T::Value>, QtJsonError> {
+        let offset = frame.table.read_u32()?;
+        trace!("Entry at offset: {:0X?}", offset);
+
+        let mut reader = Reader::new(Self::value_data(frame.data, offset)?);
+
+        let header = reader.address();
+        let value_header = reader.read_u32()?;
+        trace!(" > Value header {:032b}", value_header);
+
+        let value_type_number: u32 = value_header & 0b111;
+        let latin_or_int = ((value_header & 0b1000) >> 3) == 1;
+        let latin_key = ((value_header & 0b10000) >> 4) == 1;
+        let orig_value: u32 = (value_header & 0xFFFFFFE0) >> 5;
+
+        let value_type = QTValueType::from_header(value_header);
+
+        trace!(" > Value of type: {:?}", value_type);
+        trace!(" > Key is latin: {}", latin_key);
+        let key = Self::read_string(&mut reader, latin_key, self.options.max_string_len)?;
+        self.advance(reader.address());
+
+        trace!(" > Key is: '{}'", key);
+
+        if self.tracks_paths() {
+            self.path = pointer(&self.path, &key);
+        }
+        if let Some(encodings) = self.encodings.as_mut() {
+            encodings
+                .keys
+                .insert(self.path.clone(), StringEncoding::from_latin(latin_key));
+        }
+        trace!(" > Reading value of type: {:?}", value_type);
+
+        let skipped =
+            value_type.is_none() && self.options.on_unknown_type == UnknownTypePolicy::Skip;
+
+        if let (Some(visitor), false) = (self.visitor.as_mut(), skipped) {
+            visitor.key(&key);
+        }
+
+        if let Values::Object(_, pending) = &mut frame.values {
+            *pending = Some(key);
+        }
+
+        self.decode_value::<T>(
+            frame.index,
+            header,
+            value_type,
+            value_type_number,
+            orig_value,
+            latin_or_int,
+            frame.data,
+        )
+    }
+
+    /// Decodes the next element of an array
+    fn decode_element<'a, T: Tree<'a>>(
+        &mut self,
+        frame: &mut Frame<'a, T>,
+    ) -> Result<Decoded<'a, T::Value>, QtJsonError> {
+        // Unlike the table of an object, the table of an array does not contain offsets but
+        // the value headers themselves. Like in an object, the offset stored in such a header
+        // is relative to the start of the array and not to the previous value.
+        let header = frame.table.address();
+        let value_header = frame.table.read_u32()?;
+        trace!(" > Value header {:032b}b", value_header);
+
+        let value_type_number: u32 = value_header & 0b111;
+        let latin_or_int = ((value_header & 0b1000) >> 3) == 1;
+        let orig_value: u32 = (value_header & 0xFFFFFFE0) >> 5;
+
+        let value_type = QTValueType::from_header(value_header);
+
+        trace!(" > Reading value of type: {:?}", value_type);
+
+        if self.tracks_paths() {
+            self.path = format!("{}/{}", self.path, frame.index);
+        }
+
+        self.decode_value::<T>(
+            frame.index,
+            header,
+            value_type,
+            value_type_number,
+            orig_value,
+            latin_or_int,
+            frame.data,
+        )
+    }
+
+    /// Stores the value of the current entry of an element and moves on to the next entry.
+    /// `None` skips the entry.
+    fn finish_entry<'a, T: Tree<'a>>(&mut self, frame: &mut Frame<'a, T>, value: Option<T::Value>) {
+        self.path.truncate(frame.path);
+        let index = frame.index;
+        frame.index += 1;
+
+        // Only the base is open while its entries are finished
+        if self.depth == 1 {
+            self.report_progress();
+        }
+
+        match &mut frame.values {
+            Values::Object(values, pending) => {
+                let key = pending.take().unwrap_or_default();
+
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        trace!(" > Skipping entry '{}'", key);
+                        return;
+                    }
+                };
+
+                trace!(" > Value is: {:?}", value);
+
+                // Values reported to a visitor are not kept
+                if self.visitor.is_some() {
+                    return;
+                }
+
+                if T::insert(values, key.clone(), value) {
+                    warn!(
+                        "Duplicate key '{}' at json entry {}\nThe last value is used",
+                        key, index
+                    );
+                }
+            }
+            Values::Array(values) => {
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        trace!(" > Skipping entry {}", index);
+                        return;
+                    }
+                };
+
+                trace!(" > Value is: {:?}", value);
+
+                // Values reported to a visitor are not kept
+                if self.visitor.is_none() {
+                    values.push(value);
+                }
+            }
+        }
+    }
+
+    /// Finishes loading an element after all of its entries have been loaded
+    fn close<'a, T: Tree<'a>>(&mut self, frame: Frame<'a, T>) -> T::Value {
+        self.ancestors.pop();
+        self.depth -= 1;
+        let start = frame.data.as_ptr() as usize;
+
+        // The nested elements are covered by this one from now on
+        let end = start + frame.data.len();
+        let nested: Vec<usize> = self
+            .loaded
+            .range(start..end)
+            .map(|(&start, _)| start)
+            .collect();
+        for nested in nested {
+            self.loaded.remove(&nested);
+        }
+        self.loaded.insert(start, end);
+        self.advance(start + frame.data.len());
+        self.record_span(start..start + frame.data.len());
+
+        let value = match frame.values {
+            Values::Object(values, _) => {
+                trace!("Using object {:?}", values);
+
+                if let Some(visitor) = self.visitor.as_mut() {
+                    visitor.end_object();
+                }
+
+                T::object(values)
+            }
+            Values::Array(values) => {
+                if let Some(visitor) = self.visitor.as_mut() {
+                    visitor.end_array();
+                }
+
+                T::array(values)
+            }
+        };
+
+        trace!("{:?}", value);
+
+        value
+    }
+
+    /// This function is responsible from decoding a value from the given data.
+    /// The value will be passed from the upper declaration function and will
+    /// then be extracted here.
+    ///
+    /// This code has been created using reverse engineering. But it should work for QTJSONv1
+    ///
+    /// Nested elements are not loaded, their data is returned instead. Returns `None` if the
+    /// value should be skipped. This is the case for every scalar if a visitor is used, since
+    /// the visitor has already received it.
+    ///
+    /// `header` is the memory address of the value header. It is the span of every value
+    /// stored inside of its header.
+    #[allow(clippy::too_many_arguments)]
+    fn decode_value<'a, T: Tree<'a>>(
+        &mut self,
+        index: u32,
+        header: usize,
+        value_type: Option<QTValueType>,
+        value_type_number: u32,
+        orig_value: u32,
+        latin_or_int: bool,
+        data: &'a [u8],
+    ) -> Result<Decoded<'a, T::Value>, QtJsonError> {
+        let mut span = header..header + 4;
+
+        let scalar = match value_type {
+            Some(QTValueType::Double) => {
+                if latin_or_int {
+                    Scalar::Number(Self::inline_integer(orig_value).into())
+                } else {
+                    trace!(" > > Value is of type f64");
+                    trace!(" > > Value located at offset: {:0X?}", orig_value);
+
+                    let mut reader = Reader::new(Self::value_data(data, orig_value)?);
+                    let start = reader.address();
+                    let number = reader.read_f64()?;
+                    self.advance(reader.address());
+                    span = start..reader.address();
+
+                    // Inline integers are always finite, so only stored doubles need a check
+                    match self.options.on_non_finite {
+                        NonFinitePolicy::Null if !number.is_finite() => Scalar::Null,
+                        NonFinitePolicy::Error if !number.is_finite() => {
+                            return Err(QtJsonError::NonFiniteNumber(number))
+                        }
+                        _ => Scalar::Number(number),
+                    }
+                }
+            }
+            Some(QTValueType::String) => {
+                trace!(" > > Value located at offset: {:0X?}", orig_value);
+
+                let mut reader = Reader::new(Self::value_data(data, orig_value)?);
+                let start = reader.address();
+                if let Some(encodings) = self.encodings.as_mut() {
+                    encodings
+                        .values
+                        .insert(self.path.clone(), StringEncoding::from_latin(latin_or_int));
+                }
+                // The flag for the key of an entry is a different bit, the value has its own
+                let string =
+                    Self::read_string(&mut reader, latin_or_int, self.options.max_string_len)?;
+                self.advance(reader.address());
+                span = start..reader.address();
+
+                Scalar::String(string)
+            }
+            Some(QTValueType::Object) | Some(QTValueType::Array) => {
+                trace!(" > > Value located at offset: {:0X?}", orig_value);
+
+                // A nested element starting at the offset 0 would be the element itself. Every
+                // other offset inside the header overlaps the header of the element as well.
+                if (orig_value as usize) < ELEMENT_HEADER_SIZE {
+                    return Err(QtJsonError::InvalidData(format!(
+                        "A nested element at offset {} overlaps the header of its parent",
+                        orig_value
+                    )));
+                }
+
+                trace!(" > > Trimming {} bytes from object top", orig_value);
+                let encapsulated = Self::value_data(data, orig_value)?;
+
+                return Ok(Decoded::Element(encapsulated));
+            }
+            Some(QTValueType::Bool) => Scalar::Bool(orig_value != 0),
+            Some(QTValueType::Null) => Scalar::Null,
+            Some(QTValueType::Undefined) => Scalar::Undefined,
+            None => {
+                debug!("Value type: {:#0X}", value_type_number);
+                debug!("Value value: {:#04X}", orig_value);
+
+                match self.options.on_unknown_type {
+                    UnknownTypePolicy::Warn => {
+                        warn!("Could not parse value at json entry {}\nContinuing. But this might have unacceptable impact", index);
+                        Scalar::Undefined
+                    }
+                    UnknownTypePolicy::Error => {
+                        return Err(QtJsonError::UnknownValueType(value_type_number))
+                    }
+                    UnknownTypePolicy::Skip => {
+                        warn!("Skipping value of unknown type at json entry {}", index);
+                        return Ok(Decoded::Value(None));
+                    }
+                }
+            }
+        };
+
+        let scalar = match scalar {
+            Scalar::Undefined if self.options.collapse_undefined_to_null => Scalar::Null,
+            scalar => scalar,
+        };
+
+        self.record_span(span);
+
+        self.stats.nodes += 1;
+        if let Scalar::String(_) = scalar {
+            self.stats.strings += 1;
+        }
+
+        if let Some(visitor) = self.visitor.as_mut() {
+            visitor.value(&Borrowed::scalar(scalar));
+            return Ok(Decoded::Value(None));
+        }
+
+        Ok(Decoded::Value(Some(T::scalar(scalar))))
+    }
+
+    /// Returns the number of bytes the table of an element with the given number of entries
+    /// occupies, or `None` if it does not fit into an `usize`. A length read from a header
+    /// can be up to 2^31 - 1, which overflows on 32 bit targets.
+    fn table_size(len: usize) -> Option<usize> {
+        len.checked_mul(4)
+    }
+
+    /// Decodes an integer stored directly in the value header.
+    ///
+    /// Qt stores these as a signed 27 bit integer (`qle_signedbitfield<5, 27>`) in the upper
+    /// bits of the header, so the value needs to be sign extended.
+    fn inline_integer(value: u32) -> i32 {
+        ((value << 5) as i32) >> 5
+    }
+
+    /// Returns the data of a value located at the given offset relative to the element
+    fn value_data(data: &[u8], offset: u32) -> Result<&[u8], QtJsonError> {
+        data.get(offset as usize..).ok_or(QtJsonError::OutOfBounds)
+    }
+
+    /**
+     * reads a string.
+     * This class is capable of reading a string in UTF16 and UTF8
+     *
+     * Latin strings consisting of ASCII characters only are borrowed from the data.
+     * Strings longer than `max_len` characters are rejected before they are decoded.
+     */
+    fn read_string<'a>(
+        reader: &mut Reader<'a>,
+        latin: bool,
+        max_len: Option<usize>,
+    ) -> Result<Cow<'a, str>, QtJsonError> {
+        Self::read_string_with::<Endianess>(reader, latin, max_len)
+    }
+
+    /// Reads a string like [`Parser::read_string`], but reads the length and the UTF16 code
+    /// units using the given byte order. Documents in another byte order than [`Endianess`]
+    /// need to decode their strings with it as well.
+    fn read_string_with<'a, B: ByteOrder>(
+        reader: &mut Reader<'a>,
+        latin: bool,
+        max_len: Option<usize>,
+    ) -> Result<Cow<'a, str>, QtJsonError> {
+        // Qt stores the length of a latin string in 16 bits and the one of an UTF16 string in 32 bits
+        let key_len = match latin {
+            true => B::read_u16(reader.read_bytes(2)?) as usize,
+            false => B::read_u32(reader.read_bytes(4)?) as usize,
+        };
+
+        trace!(" --> Reading string, latin:{}, len:{}", latin, key_len);
+
+        // The length of an UTF16 string counts code units, so a character outside of the
+        // basic multilingual plane counts twice
+        if let Some(max_len) = max_len {
+            if key_len > max_len {
+                return Err(QtJsonError::StringLimitExceeded(max_len));
+            }
+        }
+
+        // The length is checked up front, so a crafted length fails with a meaningful error.
+        // The byte length of an UTF16 string can overflow on 32 bit targets.
+        let byte_len = match latin {
+            true => Some(key_len),
+            false => key_len.checked_mul(2),
+        };
+        let byte_len = match byte_len {
+            Some(byte_len) if byte_len <= reader.remaining() => byte_len,
+            _ => {
+                return Err(QtJsonError::InvalidData(format!(
+                    "The string claims a length of {}, but only {} bytes are available",
+                    key_len,
+                    reader.remaining()
+                )))
+            }
+        };
+        // A latin string is a Latin-1 (ISO-8859-1) encoded string array. So every character is
+        // 8 bits long and maps directly to the unicode code point of the same value.
+        if latin {
+            let bytes = reader.read_bytes(byte_len)?;
+
+            // ASCII is the common subset of Latin-1 and UTF8
+            let string = match core::str::from_utf8(bytes) {
+                Ok(s) if bytes.is_ascii() => Cow::Borrowed(s),
+                _ => Cow::Owned(bytes.iter().map(|&b| char::from(b)).collect()),
+            };
+            reader.align();
+
+            Ok(string)
+        } else {
+            // By definition any string in JavaScript is UTF16 encoded else.
+            let bytes = reader.read_bytes(byte_len)?;
+            let mut units = bytes.chunks_exact(2).map(B::read_u16).peekable();
+
+            // U+FFFE is a noncharacter, so a leading 0xFFFE is a byte order mark announcing that
+            // the string has been stored in the opposite byte order of the document. A leading
+            // 0xFEFF matches the document and is kept, as it is a valid zero width no-break space.
+            let swapped = units.next_if_eq(&0xFFFE).is_some();
+            let units = units.map(|unit| match swapped {
+                true => unit.swap_bytes(),
+                false => unit,
+            });
+
+            // The path is not known here, it is added while the error leaves the elements
+            let mut position = usize::from(swapped);
+            let mut string = String::with_capacity(byte_len / 2);
+            for c in char::decode_utf16(units) {
+                let c = c.map_err(|_| QtJsonError::InvalidUtf16 {
+                    path: String::new(),
+                    position,
+                })?;
+                position += c.len_utf16();
+                string.push(c);
+            }
+            reader.align();
+
+            Ok(Cow::Owned(string))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::borrow::Cow;
+
+    use byteorder::{BigEndian, ByteOrder};
+
+    use crate::parser::Parser;
+    use crate::reader::Reader;
+    use crate::QtJsonError;
+
+    #[test]
+    fn test_table_size() {
+        let len = (u32::MAX >> 1) as usize;
+        assert_eq!(Parser::table_size(len), len.checked_mul(4));
+        assert_eq!(Parser::table_size(0), Some(0));
+        assert_eq!(Parser::table_size(usize::MAX / 4), Some(usize::MAX - 3));
+        assert_eq!(Parser::table_size(usize::MAX / 4 + 1), None);
+    }
+
+    #[test]
+    fn test_inline_integer() {
+        assert_eq!(Parser::inline_integer(1), 1);
+        assert_eq!(Parser::inline_integer(1_000_000), 1_000_000);
+        assert_eq!(Parser::inline_integer(0x3FF_FFFF), 67_108_863);
+        // The header has already been shifted, so only the lower 27 bits are set
+        assert_eq!(Parser::inline_integer(0x7FF_FFFF), -1);
+        assert_eq!(Parser::inline_integer(0x400_0001), -67_108_863);
+        assert_eq!(Parser::inline_integer(0x400_0000), -67_108_864);
+    }
+
+    #[test]
+    fn test_read_string_skips_padding() {
+        // "abc" occupies 5 bytes and is followed by 3 bytes of padding
+        let data = b"\x03\x00abc\x00\x00\x00\x07\x00\x00\x00\x01\x00d\x00";
+        let mut reader = Reader::new(data);
+
+        assert_eq!(Parser::read_string(&mut reader, true, None).unwrap(), "abc");
+        assert_eq!(reader.read_u32().unwrap(), 7);
+        assert_eq!(Parser::read_string(&mut reader, true, None).unwrap(), "d");
+        assert!(reader.read_bytes(1).is_err());
+
+        // The padding behind the last string may be missing
+        let mut reader = Reader::new(b"\x01\x00d");
+        assert_eq!(Parser::read_string(&mut reader, true, None).unwrap(), "d");
+    }
+
+    #[test]
+    fn test_read_string_limit() {
+        let mut reader = Reader::new(b"\x03\x00abc\x00");
+        assert_eq!(
+            Parser::read_string(&mut reader, true, Some(3)).unwrap(),
+            "abc"
+        );
+
+        let mut reader = Reader::new(b"\x03\x00abc\x00");
+        assert!(matches!(
+            Parser::read_string(&mut reader, true, Some(2)),
+            Err(QtJsonError::StringLimitExceeded(2))
+        ));
+
+        // The emoji occupies two UTF16 code units
+        let mut reader = Reader::new(b"\x02\x00\x00\x00\x3D\xD8\x00\xDE");
+        assert!(matches!(
+            Parser::read_string(&mut reader, false, Some(1)),
+            Err(QtJsonError::StringLimitExceeded(1))
+        ));
+    }
+
+    #[test]
+    fn test_read_string_borrows_ascii() {
+        let mut reader = Reader::new(b"\x03\x00abc\x00");
+        assert!(matches!(
+            Parser::read_string(&mut reader, true, None).unwrap(),
+            Cow::Borrowed("abc")
+        ));
+
+        let mut reader = Reader::new(b"\x01\x00\xF6\x00");
+        assert!(matches!(
+            Parser::read_string(&mut reader, true, None).unwrap(),
+            Cow::Owned(ref s) if s == "ö"
+        ));
+    }
+
+    #[test]
+    fn test_read_utf16_string() {
+        let mut reader = Reader::new(b"\x02\x00\x00\x00\x3D\xD8\x00\xDE\x01\x00\x00\x00");
+
+        let string = Parser::read_string(&mut reader, false, None).unwrap();
+        assert_eq!(string, "\u{1F600}");
+        assert!(matches!(string, Cow::Owned(_)));
+        assert_eq!(reader.read_u32().unwrap(), 1);
+
+        // An unpaired surrogate
+        let mut reader = Reader::new(b"\x01\x00\x00\x00\x3D\xD8");
+        assert!(Parser::read_string(&mut reader, false, None).is_err());
+    }
+
+    #[test]
+    fn test_read_big_endian_utf16_string() {
+        let mut reader = Reader::new(b"\x00\x00\x00\x02\xD8\x3D\xDE\x00\x00\x00\x00\x01");
+
+        let string = Parser::read_string_with::<BigEndian>(&mut reader, false, None).unwrap();
+        assert_eq!(string, "\u{1F600}");
+        assert_eq!(BigEndian::read_u32(reader.read_bytes(4).unwrap()), 1);
+
+        // Latin strings only differ in the byte order of the 16 bit length
+        let mut reader = Reader::new(b"\x00\x03abc");
+        assert_eq!(
+            Parser::read_string_with::<BigEndian>(&mut reader, true, None).unwrap(),
+            "abc"
+        );
+    }
+
+    #[test]
+    fn test_read_utf16_string_with_bom() {
+        // A byte order mark matching the document is a part of the string
+        let mut reader =
+            Reader::new(b"\x03\x00\x00\x00\xFF\xFE\x3D\xD8\x00\xDE\x00\x00\x01\x00\x00\x00");
+
+        let string = Parser::read_string(&mut reader, false, None).unwrap();
+        assert_eq!(string, "\u{FEFF}\u{1F600}");
+        assert_eq!(reader.read_u32().unwrap(), 1);
+
+        // A byte order mark announcing that the string has been stored as big endian
+        let mut reader =
+            Reader::new(b"\x03\x00\x00\x00\xFE\xFF\xD8\x3D\xDE\x00\x00\x00\x01\x00\x00\x00");
+
+        let string = Parser::read_string(&mut reader, false, None).unwrap();
+        assert_eq!(string, "\u{1F600}");
+        assert_eq!(reader.read_u32().unwrap(), 1);
+    }
+}
@@ -0,0 +1,64 @@
+use byteorder::ByteOrder;
+
+use crate::{align_to_4, Endianess, QtJsonError};
+
+/// A minimal cursor over a byte slice.
+///
+/// This replaces `std::io::Cursor` so the parser does not depend on `std`.
+/// Every read past the end of the data returns [`QtJsonError::OutOfBounds`].
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data, position: 0 }
+    }
+
+    /// Takes the next `len` bytes from the data and advances the cursor
+    fn take(&mut self, len: usize) -> Result<&'a [u8], QtJsonError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(QtJsonError::OutOfBounds)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(QtJsonError::OutOfBounds)?;
+
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    /// Returns the number of bytes behind the cursor
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Returns the memory address of the cursor. This relates the positions of readers over
+    /// different parts of the same data.
+    pub(crate) fn address(&self) -> usize {
+        self.data.as_ptr() as usize + self.position
+    }
+
+    /// Borrows the next `len` bytes from the data
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], QtJsonError> {
+        self.take(len)
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, QtJsonError> {
+        Ok(Endianess::read_u32(self.take(4)?))
+    }
+
+    pub(crate) fn read_f64(&mut self) -> Result<f64, QtJsonError> {
+        Ok(Endianess::read_f64(self.take(8)?))
+    }
+
+    /// Skips the padding up to the next 4 byte boundary.
+    /// The padding behind the last value of the data may be missing.
+    pub(crate) fn align(&mut self) {
+        self.position = align_to_4(self.position).min(self.data.len());
+    }
+}
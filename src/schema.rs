@@ -0,0 +1,251 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::flatten::pointer;
+use crate::QJSONDocument;
+
+/// The expected shape of a value, see [`QJSONDocument::validate_schema`].
+///
+/// ```
+/// use qt_json::Schema;
+///
+/// let schema = Schema::object([("name", Schema::String), ("count", Schema::Number)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Accepts every value
+    Any,
+    /// A string
+    String,
+    /// A number
+    Number,
+    /// A bool
+    Bool,
+    /// A null value
+    Null,
+    /// An array whose values all match the schema
+    Array(Box<Schema>),
+    /// An object containing an entry for every key matching its schema. Entries which are not
+    /// part of the schema are accepted.
+    Object(BTreeMap<String, Schema>),
+    /// A value matching the schema. As the schema of an object entry, the entry may also be
+    /// missing.
+    Optional(Box<Schema>),
+}
+
+impl Schema {
+    /// Creates the schema of an array whose values all match the schema
+    pub fn array(values: Schema) -> Self {
+        Schema::Array(Box::new(values))
+    }
+
+    /// Creates the schema of an object containing the given entries
+    pub fn object<K: Into<String>, I: IntoIterator<Item = (K, Schema)>>(entries: I) -> Self {
+        Schema::Object(
+            entries
+                .into_iter()
+                .map(|(key, schema)| (key.into(), schema))
+                .collect(),
+        )
+    }
+
+    /// Creates the schema of a value which may be missing from its object
+    pub fn optional(schema: Schema) -> Self {
+        Schema::Optional(Box::new(schema))
+    }
+
+    /// Returns the name of the expected type like [`JsonValue::type_name`]
+    fn type_name(&self) -> &'static str {
+        match self {
+            Schema::Any => "any",
+            Schema::String => "string",
+            Schema::Number => "number",
+            Schema::Bool => "bool",
+            Schema::Null => "null",
+            Schema::Array(_) => "array",
+            Schema::Object(_) => "object",
+            Schema::Optional(schema) => schema.type_name(),
+        }
+    }
+}
+
+/// A value not matching its [`Schema`].
+///
+/// The path is a JSON pointer like the ones of
+/// [`QJSONDocument::to_btree`](crate::QJSONDocument::to_btree), the base has an empty path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// The path of the value
+    pub path: String,
+    /// The name of the type required by the schema
+    pub expected: &'static str,
+    /// The name of the type of the value, see [`JsonValue::type_name`], or `"missing"` if an
+    /// object does not contain the entry
+    pub found: &'static str,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected {} at '{}', found {}",
+            self.expected, self.path, self.found
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+impl QJSONDocument {
+    /// Checks the document against the schema and reports every value not matching it.
+    ///
+    /// Unlike [`QJSONDocument::deserialize`] this does not stop at the first mismatch and does
+    /// not need a struct describing the document.
+    pub fn validate_schema(&self, schema: &Schema) -> Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+
+        match &self.base {
+            JsonBaseValue::Object(object) => check_object(&mut errors, "", object, schema),
+            JsonBaseValue::Array(values) => check_array(&mut errors, "", values, schema),
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+fn check_value(errors: &mut Vec<SchemaError>, path: &str, value: &JsonValue, schema: &Schema) {
+    match (schema, value) {
+        (Schema::Optional(schema), value) => check_value(errors, path, value, schema),
+        (Schema::Any, _)
+        | (Schema::String, JsonValue::String(_))
+        | (Schema::Number, JsonValue::Number(_))
+        | (Schema::Bool, JsonValue::Bool(_))
+        | (Schema::Null, JsonValue::Null) => {}
+        (Schema::Array(_), JsonValue::Array(values)) => check_array(errors, path, values, schema),
+        (Schema::Object(_), JsonValue::Object(object)) => {
+            check_object(errors, path, object, schema)
+        }
+        (schema, value) => errors.push(SchemaError {
+            path: String::from(path),
+            expected: schema.type_name(),
+            found: value.type_name(),
+        }),
+    }
+}
+
+fn check_object(errors: &mut Vec<SchemaError>, path: &str, object: &Object, schema: &Schema) {
+    let entries = match schema {
+        Schema::Any => return,
+        Schema::Optional(schema) => return check_object(errors, path, object, schema),
+        Schema::Object(entries) => entries,
+        schema => {
+            return errors.push(SchemaError {
+                path: String::from(path),
+                expected: schema.type_name(),
+                found: "object",
+            })
+        }
+    };
+
+    for (key, schema) in entries {
+        let path = pointer(path, key);
+
+        match (object.values.get(key), schema) {
+            (Some(value), schema) => check_value(errors, &path, value, schema),
+            (None, Schema::Optional(_)) => {}
+            (None, schema) => errors.push(SchemaError {
+                path,
+                expected: schema.type_name(),
+                found: "missing",
+            }),
+        }
+    }
+}
+
+fn check_array(errors: &mut Vec<SchemaError>, path: &str, values: &[JsonValue], schema: &Schema) {
+    let schema = match schema {
+        Schema::Any => return,
+        Schema::Optional(schema) => return check_array(errors, path, values, schema),
+        Schema::Array(schema) => schema,
+        schema => {
+            return errors.push(SchemaError {
+                path: String::from(path),
+                expected: schema.type_name(),
+                found: "array",
+            })
+        }
+    };
+
+    for (index, value) in values.iter().enumerate() {
+        check_value(errors, &format!("{}/{}", path, index), value, schema);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::{fixtures, Schema, SchemaError};
+
+    #[test]
+    fn test_matching_schema() {
+        let schema = Schema::array(Schema::object([
+            ("test", Schema::String),
+            ("comment", Schema::optional(Schema::String)),
+        ]));
+
+        assert_eq!(fixtures::nested_document().validate_schema(&schema), Ok(()));
+        assert_eq!(
+            fixtures::nested_document().validate_schema(&Schema::Any),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_mismatching_schema() {
+        let schema = Schema::array(Schema::object([
+            ("test", Schema::Number),
+            ("count", Schema::Number),
+        ]));
+
+        assert_eq!(
+            fixtures::nested_document().validate_schema(&schema),
+            Err(vec![
+                SchemaError {
+                    path: "/0/count".to_string(),
+                    expected: "number",
+                    found: "missing",
+                },
+                SchemaError {
+                    path: "/0/test".to_string(),
+                    expected: "number",
+                    found: "string",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mismatching_base() {
+        let schema = Schema::object([("test", Schema::String)]);
+
+        assert_eq!(
+            fixtures::nested_document().validate_schema(&schema),
+            Err(vec![SchemaError {
+                path: "".to_string(),
+                expected: "object",
+                found: "array",
+            }])
+        );
+    }
+}
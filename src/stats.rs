@@ -0,0 +1,19 @@
+/// Statistics collected while parsing a document, see [`parse_with_stats`](crate::parse_with_stats).
+///
+/// Values skipped by [`UnknownTypePolicy::Skip`](crate::UnknownTypePolicy::Skip) are not
+/// counted.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ParseStats {
+    /// The number of objects, including the base
+    pub objects: usize,
+    /// The number of arrays, including the base
+    pub arrays: usize,
+    /// The number of string values. Keys are not counted.
+    pub strings: usize,
+    /// The number of values of any type, including objects, arrays and the base
+    pub nodes: usize,
+    /// The deepest nesting level reached. The base has a depth of 1.
+    pub max_depth: usize,
+    /// The number of bytes the document occupies, including the header
+    pub bytes: usize,
+}
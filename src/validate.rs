@@ -0,0 +1,122 @@
+use alloc::format;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object};
+use crate::{QJSONDocument, QtJsonError, QT_JSON_TAG};
+
+impl QJSONDocument {
+    /// Checks the structural integrity of the document.
+    ///
+    /// A document is valid if its tag is "qbjs", its version is 1, the size of every object
+    /// equals its number of entries and no value is `Undefined`.
+    pub fn validate(&self) -> Result<(), QtJsonError> {
+        self.validate_with(false)
+    }
+
+    /// Checks the structural integrity of the document like [`QJSONDocument::validate`],
+    /// but accepts `Undefined` values if `allow_undefined` is set.
+    pub fn validate_with(&self, allow_undefined: bool) -> Result<(), QtJsonError> {
+        if self.tag != QT_JSON_TAG {
+            return Err(QtJsonError::InvalidTag(self.tag));
+        }
+
+        if self.version != 1 {
+            return Err(QtJsonError::UnsupportedVersion(self.version));
+        }
+
+        match self.base {
+            JsonBaseValue::Object(ref object) => validate_object(object, allow_undefined),
+            JsonBaseValue::Array(ref values) => values
+                .iter()
+                .try_for_each(|value| validate_value(value, allow_undefined)),
+        }
+    }
+}
+
+fn validate_object(object: &Object, allow_undefined: bool) -> Result<(), QtJsonError> {
+    if object.size as usize != object.values.len() {
+        return Err(QtJsonError::InvalidData(format!(
+            "The object size {} does not match its {} entries",
+            object.size,
+            object.values.len()
+        )));
+    }
+
+    object
+        .values
+        .values()
+        .try_for_each(|value| validate_value(value, allow_undefined))
+}
+
+fn validate_value(value: &JsonValue, allow_undefined: bool) -> Result<(), QtJsonError> {
+    match value {
+        JsonValue::Object(object) => validate_object(object, allow_undefined),
+        JsonValue::Array(values) => values
+            .iter()
+            .try_for_each(|value| validate_value(value, allow_undefined)),
+        JsonValue::Undefined if !allow_undefined => Err(QtJsonError::InvalidData(
+            "The document contains an undefined value".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::elements::{JsonBaseValue, JsonValue};
+    use crate::{fixtures, QtJsonError};
+
+    #[test]
+    fn validate_parsed_document() {
+        assert!(fixtures::nested_document().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_tampered_size() {
+        let mut document = fixtures::nested_document();
+
+        match document.base {
+            JsonBaseValue::Array(ref mut values) => match values[0] {
+                JsonValue::Object(ref mut object) => object.size = 3,
+                _ => panic!("Expected object"),
+            },
+            _ => panic!("Expected array"),
+        }
+
+        assert!(matches!(
+            document.validate(),
+            Err(QtJsonError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn validate_tampered_header() {
+        let mut document = fixtures::nested_document();
+        document.version = 2;
+
+        assert!(matches!(
+            document.validate(),
+            Err(QtJsonError::UnsupportedVersion(2))
+        ));
+
+        document.version = 1;
+        document.tag = 0;
+
+        assert!(matches!(
+            document.validate(),
+            Err(QtJsonError::InvalidTag(0))
+        ));
+    }
+
+    #[test]
+    fn validate_undefined() {
+        let mut document = fixtures::nested_document();
+
+        match document.base {
+            JsonBaseValue::Array(ref mut values) => values.push(JsonValue::Undefined),
+            _ => panic!("Expected array"),
+        }
+
+        assert!(document.validate().is_err());
+        assert!(document.validate_with(true).is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::elements::JsonValueRef;
+
+/// Receives the events of [`parse_with_visitor`](crate::parse_with_visitor) while the parser
+/// walks a document.
+///
+/// Every object starts with [`begin_object`](JsonVisitor::begin_object) and ends with
+/// [`end_object`](JsonVisitor::end_object). In between, every entry reports its key followed by
+/// either a single scalar value or the events of a nested object or array. Arrays work the same
+/// way without the keys. The entries are reported in the order they are stored in the document.
+///
+/// Every method does nothing by default, so only the interesting events need to be implemented.
+pub trait JsonVisitor {
+    /// An object with the given number of entries begins
+    fn begin_object(&mut self, _len: usize) {}
+    /// The key of the next entry of the current object
+    fn key(&mut self, _key: &str) {}
+    /// A value which is neither an object nor an array, so never a
+    /// [`JsonValueRef::Object`] or a [`JsonValueRef::Array`]. Latin strings consisting of ASCII
+    /// characters only are borrowed from the data.
+    fn value(&mut self, _value: &JsonValueRef<'_>) {}
+    /// The current object ends
+    fn end_object(&mut self) {}
+    /// An array with the given number of values begins
+    fn begin_array(&mut self, _len: usize) {}
+    /// The current array ends
+    fn end_array(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::borrow::Cow;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::elements::{JsonValue, JsonValueRef};
+    use crate::{fixtures, parse_with_visitor, JsonVisitor};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        BeginObject(usize),
+        Key(String),
+        Value(JsonValue),
+        EndObject,
+        BeginArray(usize),
+        EndArray,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+        borrowed_strings: usize,
+    }
+
+    impl JsonVisitor for Recorder {
+        fn begin_object(&mut self, len: usize) {
+            self.events.push(Event::BeginObject(len));
+        }
+
+        fn key(&mut self, key: &str) {
+            self.events.push(Event::Key(key.to_string()));
+        }
+
+        fn value(&mut self, value: &JsonValueRef<'_>) {
+            if let JsonValueRef::String(Cow::Borrowed(_)) = value {
+                self.borrowed_strings += 1;
+            }
+            self.events.push(Event::Value(value.clone().into_owned()));
+        }
+
+        fn end_object(&mut self) {
+            self.events.push(Event::EndObject);
+        }
+
+        fn begin_array(&mut self, len: usize) {
+            self.events.push(Event::BeginArray(len));
+        }
+
+        fn end_array(&mut self) {
+            self.events.push(Event::EndArray);
+        }
+    }
+
+    #[test]
+    fn test_visit_nested() {
+        let mut recorder = Recorder::default();
+        parse_with_visitor(fixtures::NESTED_DOCUMENT, &mut recorder).unwrap();
+
+        assert_eq!(
+            recorder.events,
+            vec![
+                Event::BeginArray(1),
+                Event::BeginObject(1),
+                Event::Key("test".to_string()),
+                Event::Value(JsonValue::String("yes".to_string())),
+                Event::EndObject,
+                Event::EndArray,
+            ]
+        );
+        assert_eq!(recorder.borrowed_strings, 1);
+    }
+}
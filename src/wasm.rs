@@ -0,0 +1,49 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::elements::{JsonBaseValue, JsonValue};
+use crate::QJSONDocument;
+
+/// Parses a whole document and converts its base into a JavaScript value.
+///
+/// Objects become plain JavaScript objects (not `Map`s) and arrays become JavaScript arrays.
+/// `undefined` values and numbers which are not finite are converted to `null`, just like
+/// [`JsonValue::to_serde`] does.
+///
+/// Parsing errors are returned as a string describing the error.
+#[wasm_bindgen(js_name = parseToJsValue)]
+pub fn parse_to_jsvalue(data: &[u8]) -> Result<JsValue, JsValue> {
+    let document =
+        QJSONDocument::try_from(data).map_err(|error| JsValue::from_str(&error.to_string()))?;
+
+    let base = match document.base {
+        JsonBaseValue::Object(object) => JsonValue::Object(object),
+        JsonBaseValue::Array(values) => JsonValue::Array(values),
+    };
+
+    base.to_serde()
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_bindgen::JsValue;
+
+    use crate::parse_to_jsvalue;
+
+    #[test]
+    fn test_signature() {
+        let _: fn(&[u8]) -> Result<JsValue, JsValue> = parse_to_jsvalue;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_parse_to_jsvalue() {
+        let value = parse_to_jsvalue(crate::fixtures::OBJECT_DOCUMENT).unwrap();
+        let test = js_sys::Reflect::get(&value, &JsValue::from_str("test")).unwrap();
+
+        assert_eq!(test.as_string(), Some("yes".to_string()));
+        assert!(parse_to_jsvalue(b"qbjs").is_err());
+    }
+}
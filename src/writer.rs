@@ -0,0 +1,814 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use byteorder::ByteOrder;
+
+use crate::elements::{JsonBaseValue, JsonValue, Object, StringEncoding, StringEncodings};
+use crate::flatten::pointer;
+use crate::options::WriteOptions;
+use crate::{align_to_4, Endianess, QJSONDocument, QTValueType, QtJsonError, ELEMENT_HEADER_SIZE};
+
+/// The value of a value header only has 27 bits. Every offset must fit into them.
+const MAX_VALUE: usize = (1 << 27) - 1;
+
+impl QJSONDocument {
+    /// Serializes the document into the Qt binary JSON format.
+    ///
+    /// The output uses the compact layout Qt itself produces: Every value is stored directly
+    /// behind its entry and the offset table is located at the end of each element.
+    /// Small integers are stored inline in the value header like Qt does. Every other number is
+    /// stored as a 64 bit float.
+    ///
+    /// Parsing the output with [`from_binary`](QJSONDocument::from_binary) yields an equal
+    /// document. The only exceptions are NaN, which never equals itself, and strings starting
+    /// with U+FFFE, which the parser reads as a byte order mark of the opposite byte order.
+    pub fn to_binary(&self) -> Result<Vec<u8>, QtJsonError> {
+        self.to_binary_with_options(&WriteOptions::default())
+    }
+
+    /// Serializes the document like [`to_binary`](QJSONDocument::to_binary) using the given
+    /// options
+    pub fn to_binary_with_options(&self, options: &WriteOptions) -> Result<Vec<u8>, QtJsonError> {
+        debug!("[QBJS] Serializing document");
+
+        let layout = self.layout(options.string_encodings.as_ref());
+
+        // The size is known in advance, so a document exceeding the limit is never built
+        if let Some(limit) = options.max_output_size {
+            if layout.document_size() > limit {
+                return Err(QtJsonError::OutputSizeExceeded(limit));
+            }
+        }
+
+        let mut out = Vec::with_capacity(layout.document_size());
+
+        self.emit(&mut out, &layout)?;
+
+        debug!("[QBJS] Serializing finished!");
+
+        Ok(out)
+    }
+
+    /// Serializes the document like [`to_binary`](QJSONDocument::to_binary), but writes it
+    /// directly into the writer instead of building a buffer first.
+    ///
+    /// The data is written in many small chunks, so an unbuffered writer like a `File`
+    /// should be wrapped into a `BufWriter`.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> Result<(), QtJsonError> {
+        debug!("[QBJS] Writing document");
+
+        let layout = self.layout(None);
+        self.emit(&mut IoOutput(writer), &layout)?;
+
+        debug!("[QBJS] Writing finished!");
+
+        Ok(())
+    }
+
+    /// Returns the number of bytes [`to_binary`](QJSONDocument::to_binary) emits for this
+    /// document, including the padding.
+    pub fn encoded_size(&self) -> usize {
+        self.layout(None).document_size()
+    }
+
+    /// Calculates the size of every element and chooses the storage of every string, using the
+    /// given encodings where possible
+    fn layout<'e>(&self, encodings: Option<&'e StringEncodings>) -> Layout<'e> {
+        let mut layout = Layout {
+            encodings,
+            sizes: Vec::new(),
+            latin: Vec::new(),
+        };
+
+        match self.base {
+            JsonBaseValue::Object(ref object) => layout.measure_object(object, ""),
+            JsonBaseValue::Array(ref values) => layout.measure_array(values, ""),
+        };
+
+        layout
+    }
+
+    /// Writes the whole document using the layout calculated by [`QJSONDocument::layout`]
+    fn emit<O: Output>(&self, out: &mut O, layout: &Layout) -> Result<(), QtJsonError> {
+        let mut emitter = Emitter {
+            out,
+            position: 0,
+            sizes: layout.sizes.iter(),
+            latin: layout.latin.iter(),
+        };
+
+        emitter.push_u32(self.tag)?;
+        emitter.push_u32(self.version)?;
+
+        match self.base {
+            JsonBaseValue::Object(ref object) => emitter.write_object(object),
+            JsonBaseValue::Array(ref values) => emitter.write_array(values),
+        }
+    }
+}
+
+/// Qt looks up keys using a binary search. Therefore the entries need to be sorted.
+fn sorted_entries(object: &Object) -> Vec<(&String, &JsonValue)> {
+    let mut entries: Vec<(&String, &JsonValue)> = object.values.iter().collect();
+    entries.sort_by(|a, b| qt_key_order(a.0, b.0));
+
+    entries
+}
+
+/// Compares two keys the way Qt orders the entries of an object.
+///
+/// Qt compares the UTF16 code units of the keys one by one, and a key which is a prefix of the
+/// other comes first (`QJsonPrivate::String::operator<`). Latin keys compare the same, since
+/// each of their bytes is the code unit of the character. The order of `str` compares code
+/// points instead, which differs for code units of surrogate pairs, as they sort in front of
+/// the characters from `U+E000` to `U+FFFF`.
+fn qt_key_order(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+/// Everything decided before the first byte is written, in the order it is written
+struct Layout<'e> {
+    /// The encodings to follow. Without them every string is stored as latin if possible.
+    encodings: Option<&'e StringEncodings>,
+    /// The size of every element. The first entry is the size of the base.
+    sizes: Vec<usize>,
+    /// Whether a key or a string value is stored as latin
+    latin: Vec<bool>,
+}
+
+impl Layout<'_> {
+    /// The size of the whole document, including the header
+    fn document_size(&self) -> usize {
+        8 + self.sizes[0]
+    }
+
+    /// Returns the JSON pointer of a child. The pointers are only needed to look up encodings,
+    /// so they are not built without them.
+    fn child_path<F: FnOnce() -> String>(&self, path: F) -> String {
+        match self.encodings {
+            Some(_) => path(),
+            None => String::new(),
+        }
+    }
+
+    /// Decides if a string is stored as latin. A recorded latin encoding is only followed if
+    /// every character fits into a single byte.
+    fn choose_latin(&mut self, s: &str, encoding: Option<&StringEncoding>) -> bool {
+        let latin = encoding != Some(&StringEncoding::Utf16) && is_latin(s);
+        self.latin.push(latin);
+
+        latin
+    }
+
+    fn measure_object(&mut self, object: &Object, path: &str) -> usize {
+        let index = self.sizes.len();
+        self.sizes.push(0);
+
+        let mut size = ELEMENT_HEADER_SIZE + 4 * object.values.len();
+        for (key, value) in sorted_entries(object) {
+            let path = self.child_path(|| pointer(path, key));
+
+            let encoding = self
+                .encodings
+                .and_then(|encodings| encodings.keys.get(&path));
+            let latin_key = self.choose_latin(key, encoding);
+
+            size += 4 + string_size(key, latin_key) + self.measure_value(value, &path);
+        }
+
+        self.sizes[index] = size;
+        size
+    }
+
+    fn measure_array(&mut self, values: &[JsonValue], path: &str) -> usize {
+        let index = self.sizes.len();
+        self.sizes.push(0);
+
+        let mut size = ELEMENT_HEADER_SIZE + 4 * values.len();
+        for (i, value) in values.iter().enumerate() {
+            let path = self.child_path(|| format!("{}/{}", path, i));
+            size += self.measure_value(value, &path);
+        }
+
+        self.sizes[index] = size;
+        size
+    }
+
+    /// The number of bytes [`Emitter::write_value`] writes in addition to the value header
+    fn measure_value(&mut self, value: &JsonValue, path: &str) -> usize {
+        match value {
+            JsonValue::Null | JsonValue::Undefined | JsonValue::Bool(_) => 0,
+            JsonValue::Number(n) => match number_encoding(*n) {
+                NumberEncoding::Inline(_) => 0,
+                NumberEncoding::Double => 8,
+            },
+            JsonValue::String(s) => {
+                let encoding = self
+                    .encodings
+                    .and_then(|encodings| encodings.values.get(path));
+                let latin = self.choose_latin(s, encoding);
+
+                string_size(s, latin)
+            }
+            JsonValue::Object(object) => self.measure_object(object, path),
+            JsonValue::Array(values) => self.measure_array(values, path),
+        }
+    }
+}
+
+/// The number of bytes [`Emitter::write_string`] writes, including the padding
+fn string_size(s: &str, latin: bool) -> usize {
+    let size = match latin {
+        true => 2 + s.chars().count(),
+        false => 4 + 2 * s.encode_utf16().count(),
+    };
+
+    align_to_4(size)
+}
+
+/// Receives the serialized bytes
+trait Output {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), QtJsonError>;
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), QtJsonError> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct IoOutput<W>(W);
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Output for IoOutput<W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), QtJsonError> {
+        Ok(self.0.write_all(bytes)?)
+    }
+}
+
+/// Writes the elements front to back. Since the size of every element is known in advance, no
+/// written byte is ever patched.
+struct Emitter<'o, 's, O> {
+    out: &'o mut O,
+    /// The number of bytes written so far
+    position: usize,
+    /// The remaining element sizes of the [`Layout`]
+    sizes: core::slice::Iter<'s, usize>,
+    /// The remaining string storages of the [`Layout`]
+    latin: core::slice::Iter<'s, bool>,
+}
+
+impl<O: Output> Emitter<'_, '_, O> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), QtJsonError> {
+        self.out.write(bytes)?;
+        self.position += bytes.len();
+
+        Ok(())
+    }
+
+    fn push_u16(&mut self, value: u16) -> Result<(), QtJsonError> {
+        let mut buffer = [0; 2];
+        Endianess::write_u16(&mut buffer, value);
+        self.write(&buffer)
+    }
+
+    fn push_u32(&mut self, value: u32) -> Result<(), QtJsonError> {
+        let mut buffer = [0; 4];
+        Endianess::write_u32(&mut buffer, value);
+        self.write(&buffer)
+    }
+
+    fn push_f64(&mut self, value: f64) -> Result<(), QtJsonError> {
+        let mut buffer = [0; 8];
+        Endianess::write_f64(&mut buffer, value);
+        self.write(&buffer)
+    }
+
+    /// Returns whether the next string is stored as latin
+    fn next_latin(&mut self) -> bool {
+        *self.latin.next().expect("Every string has been measured")
+    }
+
+    /// Returns whether the value is a string stored as latin. Only strings have a storage in
+    /// the layout.
+    fn value_latin(&mut self, value: &JsonValue) -> bool {
+        match value {
+            JsonValue::String(_) => self.next_latin(),
+            _ => false,
+        }
+    }
+
+    /// Pads the output with zeros up to the next 4 byte boundary
+    fn pad(&mut self) -> Result<(), QtJsonError> {
+        let padding = align_to_4(self.position) - self.position;
+        self.write(&[0; 3][..padding])
+    }
+
+    /// Writes the element header and returns the position of the element start together with
+    /// its measured size
+    fn begin_element(
+        &mut self,
+        is_object: bool,
+        len: usize,
+    ) -> Result<(usize, usize), QtJsonError> {
+        let size = *self.sizes.next().expect("Every element has been measured");
+
+        if len > (u32::MAX >> 1) as usize || size > MAX_VALUE {
+            return Err(QtJsonError::DocumentTooLarge);
+        }
+
+        let start = self.position;
+
+        // The offset table is located at the end of the element
+        self.push_u32(size as u32)?;
+        self.push_u32(((len as u32) << 1) | is_object as u32)?;
+        self.push_u32((size - 4 * len) as u32)?;
+
+        trace!("Element size is: {:#0X}", size);
+
+        Ok((start, size))
+    }
+
+    /// Writes the offset table, which completes the element
+    fn finish_element(
+        &mut self,
+        start: usize,
+        size: usize,
+        table: &[u32],
+    ) -> Result<(), QtJsonError> {
+        for entry in table {
+            self.push_u32(*entry)?;
+        }
+
+        debug_assert_eq!(self.position - start, size);
+
+        Ok(())
+    }
+
+    fn write_object(&mut self, object: &Object) -> Result<(), QtJsonError> {
+        debug!("Writing object ..");
+
+        let entries = sorted_entries(object);
+
+        let (start, size) = self.begin_element(true, entries.len())?;
+        let mut table = Vec::with_capacity(entries.len());
+
+        for (key, value) in entries {
+            trace!(" > Writing entry '{}'", key);
+
+            let entry_offset = self.position - start;
+            table.push(entry_offset as u32);
+
+            // The value data is stored directly behind the key
+            let latin_key = self.next_latin();
+            let latin = self.value_latin(value);
+            let value_offset = entry_offset + 4 + string_size(key, latin_key);
+
+            let header = value_header(value, value_offset, latin)? | ((latin_key as u32) << 4);
+            self.push_u32(header)?;
+            self.write_string(key, latin_key)?;
+            self.write_value(value, latin)?;
+        }
+
+        self.finish_element(start, size, &table)
+    }
+
+    fn write_array(&mut self, values: &[JsonValue]) -> Result<(), QtJsonError> {
+        debug!("Writing array ..");
+
+        let (start, size) = self.begin_element(false, values.len())?;
+        let mut table = Vec::with_capacity(values.len());
+
+        for value in values {
+            let latin = self.value_latin(value);
+            table.push(value_header(value, self.position - start, latin)?);
+            self.write_value(value, latin)?;
+        }
+
+        self.finish_element(start, size, &table)
+    }
+
+    /// Writes the data of a value, if it needs any. Strings are stored as latin if `latin` is
+    /// set.
+    fn write_value(&mut self, value: &JsonValue, latin: bool) -> Result<(), QtJsonError> {
+        match value {
+            JsonValue::Null | JsonValue::Undefined | JsonValue::Bool(_) => Ok(()),
+            JsonValue::Number(n) => match number_encoding(*n) {
+                NumberEncoding::Inline(_) => Ok(()),
+                NumberEncoding::Double => self.push_f64(*n),
+            },
+            JsonValue::String(s) => self.write_string(s, latin),
+            JsonValue::Object(object) => self.write_object(object),
+            JsonValue::Array(values) => self.write_array(values),
+        }
+    }
+
+    /// Writes a string either as latin (u16 length followed by one byte per character)
+    /// or as UTF16 (u32 length followed by the UTF16 code units).
+    fn write_string(&mut self, s: &str, latin: bool) -> Result<(), QtJsonError> {
+        if latin {
+            self.push_u16(s.chars().count() as u16)?;
+            for c in s.chars() {
+                self.write(&[c as u8])?;
+            }
+        } else {
+            self.push_u32(s.encode_utf16().count() as u32)?;
+            for unit in s.encode_utf16() {
+                self.push_u16(unit)?;
+            }
+        }
+
+        self.pad()
+    }
+}
+
+/// Returns the value header of a value whose data is located at the given offset relative to
+/// the element
+fn value_header(value: &JsonValue, offset: usize, latin: bool) -> Result<u32, QtJsonError> {
+    let header = |value_type: QTValueType, latin_or_int: bool, value: u32| {
+        (value_type as u32) | ((latin_or_int as u32) << 3) | (value << 5)
+    };
+
+    let offset = || match offset > MAX_VALUE {
+        true => Err(QtJsonError::DocumentTooLarge),
+        false => Ok(offset as u32),
+    };
+
+    Ok(match value {
+        JsonValue::Null => header(QTValueType::Null, false, 0),
+        JsonValue::Undefined => QTValueType::Undefined as u32,
+        JsonValue::Bool(b) => header(QTValueType::Bool, false, *b as u32),
+        JsonValue::Number(n) => match number_encoding(*n) {
+            // Only the lower 27 bits of the two's complement fit into the header
+            NumberEncoding::Inline(i) => {
+                header(QTValueType::Double, true, i as u32 & MAX_VALUE as u32)
+            }
+            NumberEncoding::Double => header(QTValueType::Double, false, offset()?),
+        },
+        JsonValue::String(_) => header(QTValueType::String, latin, offset()?),
+        JsonValue::Object(_) => header(QTValueType::Object, false, offset()?),
+        JsonValue::Array(_) => header(QTValueType::Array, false, offset()?),
+    })
+}
+
+/// How a number is stored in a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NumberEncoding {
+    /// The integer is stored in the value header itself
+    Inline(i32),
+    /// The 64 bit float is stored at the offset the value header points to
+    Double,
+}
+
+/// Decides how Qt stores a number. This follows `compressedNumber` of Qt, which only inlines
+/// integers whose absolute value is at least 1 and less than 2^26. Zero, fractions and anything
+/// larger is stored as a 64 bit float.
+pub(crate) fn number_encoding(n: f64) -> NumberEncoding {
+    const FRACTION_MASK: u64 = 0x000F_FFFF_FFFF_FFFF;
+
+    let bits = n.to_bits();
+    let exponent = ((bits >> 52) & 0x7FF) as i32 - 1023;
+
+    if !(0..=25).contains(&exponent) {
+        return NumberEncoding::Double;
+    }
+
+    // Any bit of the fraction below the binary point makes the number non integral
+    let fraction = bits & FRACTION_MASK;
+    if fraction & (FRACTION_MASK >> exponent) != 0 {
+        return NumberEncoding::Double;
+    }
+
+    let magnitude = ((fraction | (1 << 52)) >> (52 - exponent)) as i32;
+
+    match bits >> 63 {
+        0 => NumberEncoding::Inline(magnitude),
+        _ => NumberEncoding::Inline(-magnitude),
+    }
+}
+
+/// Qt stores a string as latin if every character fits into a single byte
+fn is_latin(s: &str) -> bool {
+    s.chars().count() < 0x8000 && s.chars().all(|c| (c as u32) <= 0xFF)
+}
+
+#[cfg(test)]
+mod test {
+    use core::cmp::Ordering;
+
+    use super::qt_key_order;
+    use crate::elements::{JsonBaseValue, JsonValue, Map, Object, StringEncoding, StringEncodings};
+    use crate::{fixtures, DocumentBuilder, QJSONDocument, QtJsonError, WriteOptions};
+
+    #[test]
+    fn write_object() {
+        let parsed = fixtures::object_document();
+
+        assert_eq!(
+            parsed.to_binary().unwrap(),
+            fixtures::OBJECT_DOCUMENT.to_vec()
+        );
+    }
+
+    #[test]
+    fn write_number() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
+        \x33\x33\x33\x33\x33\x33\x24\x40\x82\x01\x00\x00";
+
+        let parsed = QJSONDocument::from_binary(data.to_vec()).unwrap();
+
+        assert_eq!(parsed.to_binary().unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn write_nested_object() {
+        let parsed = fixtures::nested_document();
+
+        assert_eq!(
+            parsed.to_binary().unwrap(),
+            fixtures::NESTED_DOCUMENT.to_vec()
+        );
+    }
+
+    #[test]
+    fn write_latin_string() {
+        let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x02\x00\x00\x00\x10\x00\x00\x00\x01\x00\xF6\x00\x8B\x01\x00\x00";
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![JsonValue::String("ö".to_string())]),
+        };
+
+        assert_eq!(document.to_binary().unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn write_padded_string() {
+        let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
+        \x03\x00abc\x00\x00\x00\x8B\x01\x00\x00";
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![JsonValue::String("abc".to_string())]),
+        };
+
+        assert_eq!(document.to_binary().unwrap(), data.to_vec());
+        assert_eq!(
+            QJSONDocument::from_binary(data.to_vec()).unwrap().base,
+            document.base
+        );
+    }
+
+    #[test]
+    fn write_recorded_encodings() {
+        let data = b"qbjs\x01\x00\x00\x00\x20\x00\x00\x00\x03\x00\x00\x00\x1C\x00\x00\x00\
+        \x0B\x03\x00\x00\x02\x00\x00\x00a\x00b\x00\x01\x00c\x00\x0C\x00\x00\x00";
+
+        let mut values = Map::new();
+        values.insert("ab".to_string(), JsonValue::String("c".to_string()));
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Object(Object { size: 1, values }),
+        };
+
+        // Only the key is forced into UTF16, the value falls back to latin
+        let mut encodings = StringEncodings::default();
+        encodings
+            .keys
+            .insert("/ab".to_string(), StringEncoding::Utf16);
+        let options = WriteOptions {
+            string_encodings: Some(encodings),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            document.to_binary_with_options(&options).unwrap(),
+            data.to_vec()
+        );
+    }
+
+    #[test]
+    fn write_scalars() {
+        let data = b"qbjs\x01\x00\x00\x00\x1C\x00\x00\x00\x08\x00\x00\x00\x0C\x00\x00\x00\
+        \x00\x00\x00\x00\x21\x00\x00\x00\x01\x00\x00\x00\x80\x00\x00\x00";
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![
+                JsonValue::Null,
+                JsonValue::Bool(true),
+                JsonValue::Bool(false),
+                JsonValue::Undefined,
+            ]),
+        };
+
+        assert_eq!(document.to_binary().unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn write_sorted_keys() {
+        let mut values = Map::new();
+        values.insert("b".to_string(), JsonValue::Null);
+        values.insert("a".to_string(), JsonValue::Bool(true));
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Object(Object { size: 2, values }),
+        };
+
+        let binary = document.to_binary().unwrap();
+
+        // The first entry directly follows the element header
+        assert_eq!(&binary[24..28], b"\x01\x00a\x00");
+        assert_eq!(&binary[32..36], b"\x01\x00b\x00");
+    }
+
+    #[test]
+    fn write_qt_key_order() {
+        // {"a": null, "aa": null, "ab": null, "b": null}. Qt sorts a prefix in front of the
+        // longer keys, the keys are not sorted by their length first.
+        let data = b"qbjs\x01\x00\x00\x00\x3C\x00\x00\x00\x09\x00\x00\x00\x2C\x00\x00\x00\
+        \x10\x00\x00\x00\x01\x00a\x00\x10\x00\x00\x00\x02\x00aa\x10\x00\x00\x00\x02\x00ab\
+        \x10\x00\x00\x00\x01\x00b\x00\x0C\x00\x00\x00\x14\x00\x00\x00\x1C\x00\x00\x00\x24\x00\x00\x00";
+
+        let document = DocumentBuilder::object()
+            .insert("b", JsonValue::Null)
+            .insert("ab", JsonValue::Null)
+            .insert("aa", JsonValue::Null)
+            .insert("a", JsonValue::Null)
+            .build();
+
+        assert_eq!(document.to_binary().unwrap(), data.to_vec());
+        assert_eq!(
+            QJSONDocument::from_slice(data)
+                .unwrap()
+                .to_binary()
+                .unwrap(),
+            data.to_vec()
+        );
+    }
+
+    #[test]
+    fn qt_key_order_compares_code_units() {
+        assert_eq!(qt_key_order("a", "aa"), Ordering::Less);
+        assert_eq!(qt_key_order("aa", "b"), Ordering::Less);
+        assert_eq!(qt_key_order("a", "a"), Ordering::Equal);
+
+        // A surrogate pair sorts in front of U+FF61, even though its code point is larger
+        assert_eq!(qt_key_order("\u{1F600}", "\u{FF61}"), Ordering::Less);
+        assert_eq!("\u{1F600}".cmp("\u{FF61}"), Ordering::Greater);
+    }
+
+    #[test]
+    fn encoded_size() {
+        let fixtures: [&[u8]; 4] = [
+            fixtures::OBJECT_DOCUMENT,
+            b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x02\x00\x00\x00\x14\x00\x00\x00\
+            \x33\x33\x33\x33\x33\x33\x24\x40\x82\x01\x00\x00",
+            fixtures::NESTED_DOCUMENT,
+            b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x02\x00\x00\x00\x10\x00\x00\x00\x01\x00\xF6\x00\x8B\x01\x00\x00",
+        ];
+
+        for fixture in fixtures {
+            let parsed = QJSONDocument::try_from(fixture).unwrap();
+
+            assert_eq!(parsed.encoded_size(), parsed.to_binary().unwrap().len());
+            assert_eq!(parsed.encoded_size(), fixture.len());
+        }
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![
+                JsonValue::String("\u{1F600}".to_string()),
+                JsonValue::Undefined,
+                JsonValue::Array(vec![JsonValue::Bool(true)]),
+            ]),
+        };
+
+        assert_eq!(document.encoded_size(), document.to_binary().unwrap().len());
+    }
+
+    #[test]
+    fn number_encoding() {
+        use super::{number_encoding, NumberEncoding};
+
+        assert_eq!(number_encoding(1.0), NumberEncoding::Inline(1));
+        assert_eq!(number_encoding(-1.0), NumberEncoding::Inline(-1));
+        assert_eq!(number_encoding(-42.0), NumberEncoding::Inline(-42));
+        assert_eq!(
+            number_encoding(67_108_863.0),
+            NumberEncoding::Inline(67_108_863)
+        );
+        assert_eq!(
+            number_encoding(-67_108_863.0),
+            NumberEncoding::Inline(-67_108_863)
+        );
+
+        // 2^26 and above do not fit, even though the header has 27 bits
+        assert_eq!(number_encoding(67_108_864.0), NumberEncoding::Double);
+        assert_eq!(number_encoding(134_217_728.0), NumberEncoding::Double);
+        assert_eq!(number_encoding(-67_108_864.0), NumberEncoding::Double);
+
+        assert_eq!(number_encoding(0.0), NumberEncoding::Double);
+        assert_eq!(number_encoding(-0.0), NumberEncoding::Double);
+        assert_eq!(number_encoding(0.5), NumberEncoding::Double);
+        assert_eq!(number_encoding(2.5), NumberEncoding::Double);
+        assert_eq!(number_encoding(67_108_862.5), NumberEncoding::Double);
+        assert_eq!(number_encoding(f64::NAN), NumberEncoding::Double);
+        assert_eq!(number_encoding(f64::INFINITY), NumberEncoding::Double);
+    }
+
+    #[test]
+    fn write_inline_integers() {
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![
+                JsonValue::Number(-3.0),
+                JsonValue::Number(67_108_864.0),
+            ]),
+        };
+
+        let binary = document.to_binary().unwrap();
+
+        assert_eq!(
+            binary,
+            b"qbjs\x01\x00\x00\x00\x1C\x00\x00\x00\x04\x00\x00\x00\x14\x00\x00\x00\
+            \x00\x00\x00\x00\x00\x00\x90\x41\xAA\xFF\xFF\xFF\x82\x01\x00\x00"
+        );
+        assert_eq!(document.encoded_size(), binary.len());
+        assert_eq!(
+            QJSONDocument::try_from(binary.as_slice()).unwrap(),
+            document
+        );
+    }
+
+    #[test]
+    fn max_output_size() {
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Array(vec![JsonValue::String("a".repeat(100))]),
+        };
+        let size = document.encoded_size();
+
+        let options = WriteOptions {
+            max_output_size: Some(size),
+            ..Default::default()
+        };
+        assert_eq!(
+            document.to_binary_with_options(&options).unwrap().len(),
+            size
+        );
+
+        let options = WriteOptions {
+            max_output_size: Some(size - 1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            document.to_binary_with_options(&options),
+            Err(QtJsonError::OutputSizeExceeded(limit)) if limit == size - 1
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_to() {
+        let mut values = Map::new();
+        values.insert("test".to_string(), JsonValue::String("yes".to_string()));
+        values.insert("\u{1F600}".to_string(), JsonValue::Number(1.5));
+        values.insert(
+            "nested".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::Null,
+                JsonValue::Object(Object {
+                    size: 0,
+                    values: Map::new(),
+                }),
+                JsonValue::String("\u{20AC}".to_string()),
+            ]),
+        );
+
+        let document = QJSONDocument {
+            tag: crate::QT_JSON_TAG,
+            version: 1,
+            base: JsonBaseValue::Object(Object { size: 3, values }),
+        };
+
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        document.write_to(&mut cursor).unwrap();
+
+        assert_eq!(cursor.into_inner(), document.to_binary().unwrap());
+    }
+}
@@ -0,0 +1,160 @@
+//! Inputs found by fuzzing `QJSONDocument::try_from` (see `fuzz/fuzz_targets/parse.rs`).
+//! None of them may panic or overflow the stack.
+
+use qt_json::elements::JsonValue;
+use qt_json::{DocumentBuilder, ParseOptions, QJSONDocument, QtJsonError};
+
+/// An object entry offset pointing far behind the end of the object
+#[test]
+fn entry_offset_out_of_bounds() {
+    let data = b"qbjs\x01\x00\x00\x00$\x00\x00\x00\x03\x00\x00\x00 \x00\x00\x00\x1B\x03\x00\x00\
+    \x04\x00test\x00\x00\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x03\x00yes\x00\x00\x00\"\x00\x00\x00";
+
+    assert!(matches!(
+        QJSONDocument::try_from(&data[..]),
+        Err(QtJsonError::OutOfBounds)
+    ));
+}
+
+/// A nested object pointing back at its own start
+#[test]
+fn self_referencing_element() {
+    let data = b"qbjs\x01\x00\x00\x004\x00\x00\x00\x02\x00\x00\x000\x00\x00\x00$\x00\x00\x00\
+    \x03\x00\x00 \x00\x00\x00[\x1B\x00\x00\x00\x1B\x03\x00\x00\x04\x00test\x00\x00\x03\x00yes\
+    \x00\x00\x00\x0C\x00\x00\x00";
+
+    assert!(QJSONDocument::try_from(&data[..]).is_err());
+}
+
+/// An object containing itself as the value of its only entry
+#[test]
+fn object_containing_itself() {
+    let data = b"qbjs\x01\x00\x00\x00\x18\x00\x00\x00\x03\x00\x00\x00\x14\x00\x00\x00\
+    \x15\x00\x00\x00\x01\x00a\x00\x0C\x00\x00\x00";
+
+    assert!(matches!(
+        QJSONDocument::try_from(&data[..]),
+        Err(QtJsonError::InvalidData(_))
+    ));
+}
+
+/// An array whose only value claims to be an array located at the offset 0, i.e. the array
+/// itself
+#[test]
+fn nested_element_at_offset_zero() {
+    let data = b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\x02\x00\x00\x00\x0C\x00\x00\x00\
+    \x04\x00\x00\x00";
+
+    match QJSONDocument::try_from(&data[..]) {
+        Err(QtJsonError::InvalidData(message)) => assert!(message.contains("offset 0")),
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+/// An empty array claiming a size smaller than its own header
+#[test]
+fn element_smaller_than_header() {
+    let data = b"qbjs\x01\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00\x00\x08\x00\x00\x00";
+
+    match QJSONDocument::try_from(&data[..]) {
+        Err(QtJsonError::InvalidData(message)) => assert!(message.contains("header")),
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+/// A string claiming far more characters than the element contains
+#[test]
+fn oversized_string_length() {
+    let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x02\x00\x00\x00\x10\x00\x00\x00\
+    \xFF\xFF\x00\x00\x83\x01\x00\x00";
+
+    match QJSONDocument::try_from(&data[..]) {
+        Err(QtJsonError::InvalidData(message)) => assert!(message.contains("string")),
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+/// An UTF16 string whose byte length overflows 32 bits, so it must not wrap around to 2 bytes
+#[test]
+fn overflowing_utf16_string_length() {
+    let data = b"qbjs\x01\x00\x00\x00\x14\x00\x00\x00\x02\x00\x00\x00\x10\x00\x00\x00\
+    \x01\x00\x00\x80\x83\x01\x00\x00";
+
+    match QJSONDocument::try_from(&data[..]) {
+        Err(QtJsonError::InvalidData(message)) => assert!(message.contains("string")),
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+/// Arrays whose two entries share the same nested array at every level. Loading every entry
+/// separately would decode the innermost array 2^depth times.
+fn shared_siblings(depth: usize) -> Vec<u8> {
+    let words =
+        |words: &[u32]| -> Vec<u8> { words.iter().flat_map(|word| word.to_le_bytes()).collect() };
+
+    // An empty array
+    let mut element = words(&[12, 0, 12]);
+
+    for _ in 0..depth {
+        let len = element.len() as u32;
+        // Both entries are arrays located right behind the header
+        let entry = 4 | (12 << 5);
+
+        let mut parent = words(&[12 + len + 8, 2 << 1, 12 + len]);
+        parent.extend(element);
+        parent.extend(words(&[entry, entry]));
+        element = parent;
+    }
+
+    let mut data = b"qbjs\x01\x00\x00\x00".to_vec();
+    data.extend(element);
+    data
+}
+
+#[test]
+fn shared_sibling_elements() {
+    let data = shared_siblings(22);
+
+    match QJSONDocument::try_from(data.as_slice()) {
+        Err(QtJsonError::InvalidData(message)) => assert!(message.contains("overlaps")),
+        other => panic!("Expected InvalidData, got {:?}", other),
+    }
+}
+
+fn nested_arrays(depth: usize) -> Vec<u8> {
+    let mut value = JsonValue::Null;
+
+    for _ in 1..depth {
+        value = JsonValue::Array(vec![value]);
+    }
+
+    DocumentBuilder::array()
+        .push(value)
+        .build()
+        .to_binary()
+        .unwrap()
+}
+
+#[test]
+fn maximum_depth() {
+    let max_depth = ParseOptions::default().max_depth;
+
+    assert!(QJSONDocument::try_from(nested_arrays(max_depth).as_slice()).is_ok());
+
+    match QJSONDocument::try_from(nested_arrays(max_depth + 1).as_slice()) {
+        Err(QtJsonError::DepthLimitExceeded(depth)) => assert_eq!(depth, max_depth),
+        other => panic!("Expected DepthLimitExceeded, got {:?}", other),
+    }
+}
+
+/// An array header claiming far more entries than the data could ever hold
+#[test]
+fn oversized_entry_count() {
+    let data =
+        b"qbjs\x01\x00\x00\x00\x10\x00\x00\x00\xFE\xFF\xFF\xFF\x0C\x00\x00\x00\x80\x00\x00\x00";
+
+    assert!(matches!(
+        QJSONDocument::try_from(&data[..]),
+        Err(QtJsonError::InvalidData(_))
+    ));
+}
@@ -0,0 +1,77 @@
+//! Serializes randomly generated documents and parses them again.
+//! Every valid document must survive the round trip unchanged.
+
+// proptest is only a dev-dependency of the native targets
+#![cfg(not(target_arch = "wasm32"))]
+
+use proptest::prelude::*;
+
+use qt_json::elements::{JsonValue, Object};
+use qt_json::QJSONDocument;
+
+/// Numbers covering the inline integers, the integers just outside of them and arbitrary
+/// finite doubles. NaN is left out, since it never equals itself.
+fn number() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        (-(1 << 26)..(1 << 26)).prop_map(f64::from),
+        any::<i64>().prop_map(|n| n as f64),
+        any::<f64>().prop_filter("NaN never equals itself", |n| !n.is_nan()),
+    ]
+}
+
+/// Latin and UTF16 strings of every length, including the padding of odd lengths.
+///
+/// The parser reads a leading U+FFFE of an UTF16 string as a byte order mark of the opposite
+/// byte order, so a string starting with it can not survive the round trip.
+fn string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z]{0,9}",
+        "[\u{0}-\u{FF}]{0,9}",
+        any::<String>().prop_filter("A leading U+FFFE swaps the byte order", |s| {
+            !s.starts_with('\u{FFFE}')
+        }),
+    ]
+}
+
+/// Values nested up to four levels deep
+fn value() -> impl Strategy<Value = JsonValue> {
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        Just(JsonValue::Undefined),
+        any::<bool>().prop_map(JsonValue::Bool),
+        number().prop_map(JsonValue::Number),
+        string().prop_map(JsonValue::String),
+    ];
+
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(JsonValue::Array),
+            object(inner),
+        ]
+    })
+}
+
+fn object(values: impl Strategy<Value = JsonValue>) -> impl Strategy<Value = JsonValue> {
+    prop::collection::vec((string(), values), 0..8)
+        .prop_map(|entries| JsonValue::Object(entries.into_iter().collect::<Object>()))
+}
+
+/// Documents whose base is either an object or an array
+fn document() -> impl Strategy<Value = QJSONDocument> {
+    prop_oneof![
+        prop::collection::vec(value(), 0..8).prop_map(JsonValue::Array),
+        object(value()),
+    ]
+    .prop_map(|base| base.into_document().unwrap())
+}
+
+proptest! {
+    #[test]
+    fn binary_round_trip(document in document()) {
+        let data = document.to_binary().unwrap();
+        prop_assert_eq!(data.len(), document.encoded_size());
+
+        let parsed = QJSONDocument::from_binary(data).unwrap();
+        prop_assert_eq!(parsed.base, document.base);
+    }
+}